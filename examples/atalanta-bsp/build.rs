@@ -2,17 +2,103 @@ use std::{collections::HashSet, env, fs, path::PathBuf};
 
 const VALID_RISCV_EXTENSIONS: &[char] = &['i', 'e', 'm', 'c', 'a', 'f', 'd'];
 
+/// Collects `src/interrupt_table.rs` into `INTERRUPTS` as (name, number) pairs
+macro_rules! interrupt_table {
+    ($($(#[$meta:meta])* $name:ident = $num:literal,)*) => {
+        const INTERRUPTS: &[(&str, u16)] = &[$((stringify!($name), $num),)*];
+    };
+}
+
+include!("src/interrupt_table.rs");
+
+/// Standard S-mode interrupts that have no `Interrupt` variant but keep their
+/// riscv-rt trap entry points in the vector table
+const STANDARD_VECTORS: &[(&str, u16)] = &[
+    ("SupervisorSoft", 1),
+    ("SupervisorTimer", 5),
+    ("SupervisorExternal", 9),
+];
+
+/// Number of entries in the vector table, incl. the exception entry at [0]
+const VECTOR_TABLE_LEN: u16 = 64;
+
+/// Interrupts below this number are standard and their default trap entry
+/// points are provided by riscv-rt
+const FIRST_PLATFORM_INTERRUPT: u16 = 16;
+
 fn add_linker_script() {
     let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
 
     if cfg!(feature = "rt") {
+        // Provide default handlers for vectored traps
+        let mut memory_x = String::from_utf8(include_bytes!("memory.x").to_vec()).unwrap();
+        memory_x.push_str("\n/* Generated by build.rs from src/interrupt_table.rs */\n");
+        for (name, num) in INTERRUPTS {
+            if *num >= FIRST_PLATFORM_INTERRUPT {
+                memory_x.push_str(&format!(
+                    "PROVIDE(_start_{name}_trap = _start_DefaultHandler_trap);\n"
+                ));
+                // The software vectored trap calls the handlers directly
+                if cfg!(feature = "mnxti") {
+                    memory_x.push_str(&format!("PROVIDE({name} = DefaultHandler);\n"));
+                }
+            }
+        }
+
         // Put the linker script somewhere the linker can find it.
-        fs::write(out_dir.join("memory.x"), include_bytes!("memory.x")).unwrap();
+        fs::write(out_dir.join("memory.x"), memory_x).unwrap();
         println!("cargo:rustc-link-search={}", out_dir.display());
         println!("cargo:rerun-if-changed=memory.x");
     }
 }
 
+/// Generate the entries of the vector table in `$OUT_DIR/vector_table.S` from
+/// `src/interrupt_table.rs`
+///
+/// Entries without an interrupt are routed to `DefaultHandler`, except for the
+/// riscv-rt entry points in [STANDARD_VECTORS]. Also generates
+/// `$OUT_DIR/mnxti_handlers.S`, the table of handlers used by the `mnxti`
+/// feature.
+fn generate_vector_table() {
+    let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
+    println!("cargo:rerun-if-changed=src/interrupt_table.rs");
+
+    let mut seen = HashSet::new();
+    for (name, num) in INTERRUPTS.iter().chain(STANDARD_VECTORS) {
+        assert!(
+            (1..VECTOR_TABLE_LEN).contains(num),
+            "interrupt_table.rs: {name} = {num} does not fit in the vector table"
+        );
+        assert!(
+            seen.insert(*num),
+            "interrupt_table.rs: {num} is used more than once"
+        );
+    }
+
+    let mut out = String::from("// Generated by build.rs from interrupt_table.rs, do not edit\n");
+    for num in 1..VECTOR_TABLE_LEN {
+        let handler = INTERRUPTS
+            .iter()
+            .chain(STANDARD_VECTORS)
+            .find(|(_, n)| *n == num)
+            .map_or("DefaultHandler", |(name, _)| name);
+        out.push_str(&format!("    .word _start_{handler}_trap // {num}\n"));
+    }
+    fs::write(out_dir.join("vector_table.S"), out).unwrap();
+
+    // Handlers proper for the software vectored trap, indexed like the vector
+    // table
+    let mut out = String::from("// Generated by build.rs from interrupt_table.rs, do not edit\n");
+    for num in 0..VECTOR_TABLE_LEN {
+        let handler = INTERRUPTS
+            .iter()
+            .find(|(_, n)| *n == num)
+            .map_or("DefaultHandler", |(name, _)| name);
+        out.push_str(&format!("    .word {handler} // {num}\n"));
+    }
+    fs::write(out_dir.join("mnxti_handlers.S"), out).unwrap();
+}
+
 /// Parse the target RISC-V architecture and returns its bit width and the
 /// extension set
 ///
@@ -64,8 +150,151 @@ fn parse_extensions(target: &str, cargo_flags: &str) -> HashSet<char> {
     extensions
 }
 
+/// Parse a SystemVerilog numeric literal such as `32'h0003_0000`
+fn parse_sv_literal(lit: &str) -> u32 {
+    let lit = lit.trim().replace('_', "");
+    match lit.split_once("'h") {
+        Some((_, hex)) => u32::from_str_radix(hex, 16).unwrap(),
+        None => lit.parse().unwrap(),
+    }
+}
+
+/// Convert a SystemVerilog identifier such as `MTimerStartAddr` into a Rust
+/// constant name such as `MTIMER_START_ADDR`
+fn to_const_case(ident: &str) -> String {
+    let mut out = String::new();
+    let mut prev_lower = false;
+    for c in ident.chars() {
+        if c.is_ascii_uppercase() && prev_lower {
+            out.push('_');
+        }
+        prev_lower = c.is_ascii_lowercase() || c.is_ascii_digit();
+        out.push(c.to_ascii_uppercase());
+    }
+    out
+}
+
+/// Parse the address rules (`addr_rule_t`) and APB peripheral addresses
+/// (`*StartAddr`, `*EndAddr`) from `rt_pkg.sv`
+///
+/// Returns pairs of (SystemVerilog identifier, address).
+fn parse_rt_pkg(src: &str) -> Vec<(String, u32)> {
+    let mut addrs = Vec::new();
+
+    for line in src.lines() {
+        let line = line.trim();
+
+        // localparam addr_rule_t DmaRule  = '{ Start: 32'h0001_0000, End: 32'h0001_0080
+        // };
+        if let Some(rule) = line.strip_prefix("localparam addr_rule_t") {
+            let (name, fields) = rule.split_once('=').unwrap();
+            let fields = fields
+                .trim()
+                .trim_start_matches("'{")
+                .trim_end_matches("};");
+            for field in fields.split(',') {
+                let (key, lit) = field.split_once(':').unwrap();
+                addrs.push((
+                    format!("{}{}", name.trim(), key.trim()),
+                    parse_sv_literal(lit),
+                ));
+            }
+        }
+        // localparam int unsigned GpioStartAddr     = 32'h0003_0000;
+        else if let Some(param) = line.strip_prefix("localparam int unsigned") {
+            let Some((name, lit)) = param.split_once('=') else {
+                continue;
+            };
+            let name = name.trim();
+            if name.ends_with("Addr") {
+                addrs.push((
+                    name.to_string(),
+                    parse_sv_literal(lit.trim_end_matches(';')),
+                ));
+            }
+        }
+    }
+
+    addrs
+}
+
+/// Make sure the APB peripherals are mapped inside `ApbRule` and don't overlap
+fn check_rt_pkg(addrs: &[(String, u32)]) {
+    let get = |name: &str| {
+        addrs
+            .iter()
+            .find(|(n, _)| n == name)
+            .unwrap_or_else(|| panic!("rt_pkg.sv does not define {name}"))
+            .1
+    };
+    let (apb_start, apb_end) = (get("ApbRuleStart"), get("ApbRuleEnd"));
+
+    // APB mapping uses inclusive end addresses
+    let mut ranges: Vec<(&str, u32, u32)> = addrs
+        .iter()
+        .filter_map(|(name, start)| {
+            let periph = name.strip_suffix("StartAddr")?;
+            Some((periph, *start, get(&format!("{periph}EndAddr"))))
+        })
+        .collect();
+    ranges.sort_by_key(|(_, start, _)| *start);
+
+    for (periph, start, end) in &ranges {
+        assert!(start <= end, "rt_pkg.sv: {periph} ends before it starts");
+        assert!(
+            apb_start <= *start && *end < apb_end,
+            "rt_pkg.sv: {periph} is not mapped inside ApbRule"
+        );
+    }
+    for w in ranges.windows(2) {
+        assert!(
+            w[0].2 < w[1].1,
+            "rt_pkg.sv: {} overlaps with {}",
+            w[0].0,
+            w[1].0
+        );
+    }
+}
+
+/// Generate the memory map constants in `$OUT_DIR/rt_pkg.rs` from the RTL
+/// package, making `rt_pkg.sv` the single source of truth for the memory map
+///
+/// The path may be overridden using the `ATALANTA_RT_PKG` environment variable
+/// when building the BSP outside of the Atalanta repository.
+fn generate_memory_map() {
+    let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
+
+    println!("cargo:rerun-if-env-changed=ATALANTA_RT_PKG");
+    let rt_pkg = env::var("ATALANTA_RT_PKG")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| {
+            PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap()).join("../../src/ip/rt_pkg.sv")
+        });
+    println!("cargo:rerun-if-changed={}", rt_pkg.display());
+
+    let src = fs::read_to_string(&rt_pkg).unwrap_or_else(|e| {
+        panic!(
+            "cannot read {}: {e}. Set ATALANTA_RT_PKG to point to rt_pkg.sv",
+            rt_pkg.display()
+        )
+    });
+    let addrs = parse_rt_pkg(&src);
+    check_rt_pkg(&addrs);
+
+    let mut out = String::from("// Generated by build.rs from rt_pkg.sv, do not edit\n");
+    for (name, addr) in &addrs {
+        out.push_str(&format!(
+            "\n/// `{name}`\npub const {}: usize = {addr:#x};\n",
+            to_const_case(name)
+        ));
+    }
+    fs::write(out_dir.join("rt_pkg.rs"), out).unwrap();
+}
+
 fn main() {
     add_linker_script();
+    generate_vector_table();
+    generate_memory_map();
 
     let target = env::var("TARGET").unwrap();
     let cargo_flags = env::var("CARGO_ENCODED_RUSTFLAGS").unwrap();
@@ -31,3 +31,9 @@ pub fn generate_nested_trap_entry(input: TokenStream) -> TokenStream {
 pub fn generate_continue_nested_trap(_input: TokenStream) -> TokenStream {
     trampoline::generate_continue_nested_trap_impl()
 }
+
+/// Sa. [crate::trampoline::generate_mnxti_trap_impl]
+#[proc_macro]
+pub fn generate_mnxti_trap(_input: TokenStream) -> TokenStream {
+    trampoline::generate_mnxti_trap_impl()
+}
@@ -145,3 +145,64 @@ pub(crate) fn generate_continue_nested_trap_impl() -> TokenStream {
 
     instructions.parse().unwrap()
 }
+
+/// Generates the software vectored `_start_mnxti_trap` routine in assembly
+///
+/// Saves the caller-save registers, `mcause` and `mepc` once, then claims and
+/// services pending non-SHV interrupts via `mnxti` until none remain above the
+/// interrupted level. Handlers are looked up from `_mnxti_handlers` using the
+/// vector table entry returned by `mnxti`. Exceptions are forwarded to
+/// riscv-rt's `_start_trap`.
+pub(crate) fn generate_mnxti_trap_impl() -> TokenStream {
+    let width = 4;
+    let save_count = CALLER_SAVE_EABI.len() + 2;
+    let store_caller_save_regs = store_trap(CALLER_SAVE_EABI);
+    let load_caller_save_regs = load_trap(CALLER_SAVE_EABI);
+
+    let instructions = format!(
+        r#"
+        core::arch::global_asm!("
+            .section .trap, \"ax\"
+            .align 6                                    // mtvec requires 64-byte alignment in CLIC mode
+            .global _start_mnxti_trap
+            _start_mnxti_trap:
+                #----- Interrupts disabled on entry ---#
+                addi sp, sp, -{save_count} * {width}    // Create frame for caller save registers, mcause, and mepc
+                {store_caller_save_regs}
+                csrr x5, mcause                         // read cause into x5 / t0
+                bgez x5, 4f                             // not an interrupt => exception
+                csrr x15, mepc                          // read epc into x15 / t1 / a5
+                sw x5, {CAUSE_POS}(sp)                  // save cause / x5 / t0
+                sw x15, {EPC_POS}(sp)                   // save epc / x15 / t1 / a5
+                csrrsi a0, 0x345, 8                     // claim highest pending interrupt (mnxti) & enable interrupts
+                beqz a0, 3f                             // spurious, nothing to service
+            2:
+                #----- Interrupts enabled ---------#
+                csrr x5, 0x307                          // read vector table base (mtvt)
+                andi x5, x5, -64                        // mask out mode bits
+                sub a0, a0, x5                          // offset of the entry, i.e., 4 * id
+                la x5, _mnxti_handlers
+                add a0, a0, x5
+                lw a0, 0(a0)                            // load handler address
+                jalr ra, a0, 0                          // jump to interrupt handler proper
+                csrrsi a0, 0x345, 8                     // claim next pending interrupt, if any
+                bnez a0, 2b                             // service back-to-back without restoring context
+            3:
+                csrci mstatus, 8                        // disable interrupts
+                #----- Interrupts disabled  ---------#
+                lw x15, {EPC_POS}(sp)                   // restore epc from stack into x15 / t1 / a5
+                lw x5, {CAUSE_POS}(sp)                  // restore cause from stack into x5 / t0
+                csrw mepc, x15                          // put epc back into CSR
+                csrw mcause, x5                         // put cause back into CSR
+                {load_caller_save_regs}
+                addi sp, sp, {save_count} * {width}     // free stack frame
+                mret                                    // return from interrupt
+            4:
+                {load_caller_save_regs}
+                addi sp, sp, {save_count} * {width}     // free stack frame
+                j _start_trap                           // handle exception in riscv-rt
+            ");"#
+    );
+
+    instructions.parse().unwrap()
+}
@@ -48,33 +48,5 @@ PROVIDE(_start_SupervisorExternal_trap = _start_DefaultHandler_trap);
 PROVIDE(_start_MachineExternal_trap = _start_DefaultHandler_trap);
 */
 
-/* Provide default handlers for vectored traps */
-PROVIDE(_start_Uart_trap = _start_DefaultHandler_trap);
-PROVIDE(_start_Gpio_trap = _start_DefaultHandler_trap);
-PROVIDE(_start_SpiRxTxIrq_trap = _start_DefaultHandler_trap);
-PROVIDE(_start_SpiEotIrq_trap = _start_DefaultHandler_trap);
-PROVIDE(_start_Timer0Ovf_trap = _start_DefaultHandler_trap);
-PROVIDE(_start_Timer0Cmp_trap = _start_DefaultHandler_trap);
-PROVIDE(_start_Timer1Ovf_trap = _start_DefaultHandler_trap);
-PROVIDE(_start_Timer1Cmp_trap = _start_DefaultHandler_trap);
-PROVIDE(_start_Timer2Ovf_trap = _start_DefaultHandler_trap);
-PROVIDE(_start_Timer2Cmp_trap = _start_DefaultHandler_trap);
-PROVIDE(_start_Timer3Ovf_trap = _start_DefaultHandler_trap);
-PROVIDE(_start_Timer3Cmp_trap = _start_DefaultHandler_trap);
-PROVIDE(_start_Nmi_trap = _start_DefaultHandler_trap);
-PROVIDE(_start_Dma0_trap = _start_DefaultHandler_trap);
-PROVIDE(_start_Dma1_trap = _start_DefaultHandler_trap);
-PROVIDE(_start_Dma2_trap = _start_DefaultHandler_trap);
-PROVIDE(_start_Dma3_trap = _start_DefaultHandler_trap);
-PROVIDE(_start_Dma4_trap = _start_DefaultHandler_trap);
-PROVIDE(_start_Dma5_trap = _start_DefaultHandler_trap);
-PROVIDE(_start_Dma6_trap = _start_DefaultHandler_trap);
-PROVIDE(_start_Dma7_trap = _start_DefaultHandler_trap);
-PROVIDE(_start_Dma8_trap = _start_DefaultHandler_trap);
-PROVIDE(_start_Dma9_trap = _start_DefaultHandler_trap);
-PROVIDE(_start_Dma10_trap = _start_DefaultHandler_trap);
-PROVIDE(_start_Dma11_trap = _start_DefaultHandler_trap);
-PROVIDE(_start_Dma12_trap = _start_DefaultHandler_trap);
-PROVIDE(_start_Dma13_trap = _start_DefaultHandler_trap);
-PROVIDE(_start_Dma14_trap = _start_DefaultHandler_trap);
-PROVIDE(_start_Dma15_trap = _start_DefaultHandler_trap);
+/* Default handlers for the vectored traps of platform interrupts are appended
+   by build.rs from src/interrupt_table.rs */
@@ -2,6 +2,8 @@
 //!
 //! This CLIC uses the now obsolete MMIO interface. This may not match the new
 //! specification, available here: <https://github.com/riscv/riscv-fast-interrupt/blob/master/clic.adoc>
+pub mod config;
+pub mod info;
 pub mod intattr;
 pub mod intctl;
 pub mod intie;
@@ -9,9 +11,25 @@ pub mod intip;
 pub mod inttrig;
 pub mod smclicconfig;
 
+pub use info::ClicInfo;
 pub use intattr::{Polarity, Trig};
+pub use inttrig::TriggerAction;
 // Re-export useful riscv-pac traits
 pub use riscv_pac::{HartIdNumber, InterruptNumber, PriorityNumber};
+use riscv_peripheral::common::{Reg, RW};
+
+/// Error type for CLIC configuration
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "ufmt", derive(crate::ufmt::derive::uDebug))]
+pub enum ClicError {
+    /// Interrupt ID is not implemented, sa. [ClicInfo::num_interrupt]
+    InvalidInterrupt,
+    /// Interrupt trigger is not implemented, sa. [ClicInfo::num_trigger]
+    InvalidTrigger,
+    /// The hart's debug trigger module doesn't support interrupt triggers, or
+    /// the requested action
+    TriggerUnsupported,
+}
 
 /// Core-Local Interrupt Controller (CLIC) peripheral.
 #[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
@@ -24,6 +42,11 @@ impl Clic {
 
     const SMCLICCONFIG_OFFSET: usize = 0x0;
 
+    const CLICINFO_OFFSET: usize = crate::mmap::CLIC_CLICINFO_REG_OFFSET;
+
+    const XNXTICONF_OFFSET: usize = crate::mmap::CLIC_CLICXNXTICONF_REG_OFFSET;
+    const XNXTICONF_BIT: usize = crate::mmap::CLIC_CLICXNXTICONF_CLICXNXTICONF_BIT;
+
     const INTTRIG_OFFSET: usize = 0x40;
     const INTTRIG_SEPARATION: usize = 0x4;
 
@@ -37,15 +60,94 @@ impl Clic {
         unsafe { smclicconfig::SMCLICCONFIG::new(Self::BASE + Self::SMCLICCONFIG_OFFSET) }
     }
 
-    /// Returns the clicinttrig register for a given interrupt number.
+    /// Returns the parameterization of the CLIC, read from `clicinfo`.
+    #[inline]
+    pub fn info() -> ClicInfo {
+        ClicInfo::read(Self::BASE + Self::CLICINFO_OFFSET)
+    }
+
+    /// Check if the CLIC forwards pending non-SHV interrupts to `mnxti`.
+    #[inline]
+    pub fn nxti_forwarding() -> bool {
+        // SAFETY: valid address
+        let reg: Reg<u32, RW> =
+            unsafe { Reg::new((Self::BASE + Self::XNXTICONF_OFFSET) as *mut u32) };
+
+        reg.read_bit(Self::XNXTICONF_BIT)
+    }
+
+    /// Set whether the CLIC forwards pending non-SHV interrupts to `mnxti`.
+    ///
+    /// Required for back-to-back servicing using the `mnxti` feature.
+    #[inline]
+    pub fn set_nxti_forwarding(enable: bool) {
+        // SAFETY: valid address
+        let reg: Reg<u32, RW> =
+            unsafe { Reg::new((Self::BASE + Self::XNXTICONF_OFFSET) as *mut u32) };
+
+        if enable {
+            reg.set_bit(Self::XNXTICONF_BIT)
+        } else {
+            reg.clear_bit(Self::XNXTICONF_BIT)
+        }
+    }
+
+    /// Returns the clicinttrig register at a given trigger index.
     #[inline]
-    pub fn inttrig<I: InterruptNumber>(int_nr: I) -> inttrig::INTTRIG {
-        let addr =
-            Self::BASE + Self::INTTRIG_OFFSET + int_nr.number() as usize * Self::INTTRIG_SEPARATION;
+    pub fn inttrig(index: usize) -> inttrig::INTTRIG {
+        #[cfg(feature = "log")]
+        if index >= Self::info().num_trigger as usize {
+            log::warn!("interrupt trigger {} is not implemented", index);
+        }
+        let addr = Self::BASE + Self::INTTRIG_OFFSET + index * Self::INTTRIG_SEPARATION;
         // SAFETY: valid address
         unsafe { inttrig::INTTRIG::new(addr) }
     }
 
+    /// Sets up interrupt trigger `index` to take `action` whenever `int_nr` is
+    /// taken.
+    ///
+    /// Uses debug trigger `index` of the hart to select the action, which must
+    /// not be in use by the debugger.
+    ///
+    /// # Errors
+    ///
+    /// * [ClicError::InvalidTrigger] if `index` is beyond
+    ///   [ClicInfo::num_trigger].
+    /// * [ClicError::InvalidInterrupt] if `int_nr` is not implemented.
+    /// * [ClicError::TriggerUnsupported] if the hart cannot fire a debug
+    ///   trigger on the CLIC's signal, or cannot take `action`.
+    #[inline]
+    pub fn set_trigger<I: InterruptNumber>(
+        index: usize,
+        int_nr: I,
+        action: inttrig::TriggerAction,
+    ) -> Result<(), ClicError> {
+        let info = Self::info();
+        if index >= info.num_trigger as usize {
+            return Err(ClicError::InvalidTrigger);
+        }
+        if !info.has_interrupt(int_nr.number()) {
+            return Err(ClicError::InvalidInterrupt);
+        }
+        if !inttrig::arm_debug_trigger(index, action) {
+            inttrig::disarm_debug_trigger(index);
+            return Err(ClicError::TriggerUnsupported);
+        }
+        Self::inttrig(index).enable(int_nr);
+        Ok(())
+    }
+
+    /// Disables interrupt trigger `index` and releases the debug trigger set
+    /// up by [Clic::set_trigger].
+    #[inline]
+    pub fn clear_trigger(index: usize) {
+        if index < Self::info().num_trigger as usize {
+            Self::inttrig(index).disable();
+            inttrig::disarm_debug_trigger(index);
+        }
+    }
+
     /// Returns the interrupts pending register of a given interrupt number.
     #[inline]
     pub fn ip<I: InterruptNumber>(int_nr: I) -> intip::INTIP {
@@ -77,4 +179,36 @@ impl Clic {
         // SAFETY: valid address
         unsafe { intctl::INTCTL::new(addr) }
     }
+
+    /// Returns a builder that configures a given interrupt with a single
+    /// register write, sa. [config::IntConfig].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `int_nr` is not implemented by this CLIC, sa.
+    /// [Clic::try_configure].
+    #[inline]
+    pub fn configure<I: InterruptNumber>(int_nr: I) -> config::IntConfig<I> {
+        match Self::try_configure(int_nr) {
+            Ok(config) => config,
+            Err(_) => panic!("interrupt {} is not implemented", int_nr.number()),
+        }
+    }
+
+    /// Returns a builder that configures a given interrupt with a single
+    /// register write, sa. [config::IntConfig].
+    ///
+    /// # Errors
+    ///
+    /// Returns [ClicError::InvalidInterrupt] if `int_nr` is beyond
+    /// [ClicInfo::num_interrupt].
+    #[inline]
+    pub fn try_configure<I: InterruptNumber>(int_nr: I) -> Result<config::IntConfig<I>, ClicError> {
+        if !Self::info().has_interrupt(int_nr.number()) {
+            return Err(ClicError::InvalidInterrupt);
+        }
+        let addr = Self::BASE + Self::INT_OFFSET + int_nr.number() as usize * Self::INT_SEPARATION;
+        // SAFETY: valid address, interrupt is implemented
+        Ok(unsafe { config::IntConfig::new(int_nr, addr) })
+    }
 }
@@ -0,0 +1,211 @@
+//! CLIC interrupt configuration builder.
+//!
+//! Configures all fields of a `clicint[i]` register with a single
+//! read-modify-write, i.e., `clicintie[i]`, `clicintattr[i]` and
+//! `clicintctl[i]` are written at once. `clicintip[i]` is left as-is.
+use crate::{read_u32, write_u32};
+
+use super::{
+    intattr::Mode,
+    intctl::{CtlLayout, InvalidPriority},
+    InterruptNumber, Polarity, Trig,
+};
+
+const IP_BIT: u32 = 0b1;
+const IE_BIT: u32 = 0b1 << 8;
+const PCS_BIT: u32 = 0b1 << 12;
+const SHV_BIT: u32 = 0b1 << 16;
+const TRIG_BIT: u32 = 0b1 << 17;
+const POLARITY_BIT: u32 = 0b1 << 18;
+const MODE_BIT_IDX: u32 = 22;
+const MODE_MASK: u32 = 0b11 << MODE_BIT_IDX;
+const CTL_BIT_IDX: u32 = 24;
+const CTL_MASK: u32 = 0xff << CTL_BIT_IDX;
+
+/// Writes `word` into the `clicint[i]` register at `addr`, preserving the
+/// pending bit
+#[inline]
+fn write_clicint(addr: usize, word: u32) {
+    let ip = read_u32(addr) & IP_BIT;
+    write_u32(addr, ip | (word & !IP_BIT));
+}
+
+/// Builder for the configuration of a single interrupt, sa.
+/// [Clic::configure](super::Clic::configure)
+///
+/// Unset fields keep their current values. Nothing is written until
+/// [IntConfig::apply] or [IntConfig::enable] is called.
+///
+/// # Example
+///
+/// ```ignore
+/// let guard = unsafe {
+///     Clic::configure(Interrupt::Uart)
+///         .trigger(Trig::Edge)
+///         .polarity(Polarity::Pos)
+///         .shv(true)
+///         .level(0x88)
+///         .enable()
+/// };
+/// // ...
+/// // Restores the previous configuration
+/// drop(guard);
+/// ```
+#[must_use]
+pub struct IntConfig<I: InterruptNumber> {
+    irq: I,
+    addr: usize,
+    prev: u32,
+    word: u32,
+}
+
+impl<I: InterruptNumber> IntConfig<I> {
+    /// # Safety
+    ///
+    /// The address must point to the `clicint[i]` register of `irq`.
+    #[inline]
+    pub(crate) unsafe fn new(irq: I, addr: usize) -> Self {
+        let prev = read_u32(addr) & !IP_BIT;
+        Self {
+            irq,
+            addr,
+            prev,
+            word: prev,
+        }
+    }
+
+    #[inline]
+    fn with_bit(mut self, bit: u32, value: bool) -> Self {
+        if value {
+            self.word |= bit;
+        } else {
+            self.word &= !bit;
+        }
+        self
+    }
+
+    /// Sets the trigger type
+    #[inline]
+    pub fn trigger(self, trig: Trig) -> Self {
+        self.with_bit(TRIG_BIT, trig == Trig::Edge)
+    }
+
+    /// Sets the polarity
+    #[inline]
+    pub fn polarity(self, polarity: Polarity) -> Self {
+        self.with_bit(POLARITY_BIT, polarity == Polarity::Neg)
+    }
+
+    /// Sets selective hardware vectoring
+    #[inline]
+    pub fn shv(self, shv: bool) -> Self {
+        self.with_bit(SHV_BIT, shv)
+    }
+
+    /// Sets the privilege mode
+    #[inline]
+    pub fn mode(mut self, mode: Mode) -> Self {
+        self.word = (self.word & !MODE_MASK) | ((mode as u32) << MODE_BIT_IDX);
+        self
+    }
+
+    /// Sets the interrupt level, sa. [super::intctl::INTCTL::set_level]
+    #[inline]
+    pub fn level(mut self, level: u8) -> Self {
+        let ctl = CtlLayout::current().with_level(self.ctl(), level);
+        self.word = (self.word & !CTL_MASK) | ((ctl as u32) << CTL_BIT_IDX);
+        self
+    }
+
+    /// Sets the interrupt priority, sa.
+    /// [super::intctl::INTCTL::set_priority]
+    ///
+    /// # Errors
+    ///
+    /// Returns [InvalidPriority] if `priority` exceeds
+    /// [super::intctl::INTCTL::max_priority].
+    #[inline]
+    pub fn priority(mut self, priority: u8) -> Result<Self, InvalidPriority> {
+        let ctl = CtlLayout::current().with_priority(self.ctl(), priority)?;
+        self.word = (self.word & !CTL_MASK) | ((ctl as u32) << CTL_BIT_IDX);
+        Ok(self)
+    }
+
+    #[inline]
+    fn ctl(&self) -> u8 {
+        ((self.word & CTL_MASK) >> CTL_BIT_IDX) as u8
+    }
+
+    /// Sets the interrupt to use the PCS (hardware context save) or not
+    ///
+    /// PCS may not be implemented. A warning is logged if it doesn't stick.
+    #[inline]
+    pub fn pcs(self, pcs: bool) -> Self {
+        self.with_bit(PCS_BIT, pcs)
+    }
+
+    /// Writes the configuration with the interrupt disabled
+    #[inline]
+    pub fn apply(self) -> IntGuard<I> {
+        self.write(false)
+    }
+
+    /// Writes the configuration and enables the interrupt
+    ///
+    /// # Safety
+    ///
+    /// * Enabling an interrupt source can break mask-based critical sections.
+    #[inline]
+    pub unsafe fn enable(self) -> IntGuard<I> {
+        self.write(true)
+    }
+
+    #[inline]
+    fn write(self, enable: bool) -> IntGuard<I> {
+        let this = self.with_bit(IE_BIT, enable);
+        write_clicint(this.addr, this.word);
+
+        // PCS is WARL, it reads as zero when not implemented
+        #[cfg(feature = "log")]
+        if this.word & PCS_BIT != 0 && read_u32(this.addr) & PCS_BIT == 0 {
+            log::warn!("PCS is not supported for interrupt {}", this.irq.number());
+        }
+        IntGuard {
+            irq: this.irq,
+            addr: this.addr,
+            prev: this.prev,
+        }
+    }
+}
+
+/// Restores the configuration an interrupt had prior to [IntConfig::apply] or
+/// [IntConfig::enable] when dropped
+///
+/// Use [IntGuard::keep] to retain the new configuration instead.
+#[must_use = "dropping the guard restores the previous configuration immediately"]
+pub struct IntGuard<I: InterruptNumber> {
+    irq: I,
+    addr: usize,
+    prev: u32,
+}
+
+impl<I: InterruptNumber> IntGuard<I> {
+    /// Returns the interrupt this guard is configuring
+    #[inline]
+    pub fn irq(&self) -> I {
+        self.irq
+    }
+
+    /// Retains the new configuration
+    #[inline]
+    pub fn keep(self) {
+        core::mem::forget(self)
+    }
+}
+
+impl<I: InterruptNumber> Drop for IntGuard<I> {
+    #[inline]
+    fn drop(&mut self) {
+        write_clicint(self.addr, self.prev);
+    }
+}
@@ -0,0 +1,58 @@
+//! CLIC information register.
+use crate::{mmap::*, read_u32};
+
+/// Contents of the read-only `clicinfo` register.
+///
+/// Describes how this CLIC instance was parameterized, sa. [Clic::info].
+///
+/// [Clic::info]: super::Clic::info
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "ufmt", derive(crate::ufmt::derive::uDebug))]
+pub struct ClicInfo {
+    /// Number of interrupt inputs, i.e., interrupt IDs `0..num_interrupt` are
+    /// implemented
+    pub num_interrupt: u16,
+    /// Architecture (upper 4 bits) and implementation (lower 4 bits) version
+    pub version: u8,
+    /// Number of bits implemented in `clicintctl[i]`
+    pub intctlbits: u8,
+    /// Number of interrupt triggers, i.e., `clicinttrig[0..num_trigger]` are
+    /// implemented
+    pub num_trigger: u8,
+}
+
+impl ClicInfo {
+    /// Reads the `clicinfo` register at `address`.
+    #[inline]
+    pub(crate) fn read(address: usize) -> Self {
+        let info = read_u32(address) as usize;
+        let field = |ofs: usize, mask: usize| (info >> ofs) & mask;
+        Self {
+            num_interrupt: field(
+                CLIC_CLICINFO_NUM_INTERRUPT_OFFSET,
+                CLIC_CLICINFO_NUM_INTERRUPT_MASK,
+            ) as u16,
+            version: field(CLIC_CLICINFO_VERSION_OFFSET, CLIC_CLICINFO_VERSION_MASK) as u8,
+            intctlbits: field(
+                CLIC_CLICINFO_CLICINTCTLBITS_OFFSET,
+                CLIC_CLICINFO_CLICINTCTLBITS_MASK,
+            ) as u8,
+            num_trigger: field(
+                CLIC_CLICINFO_NUM_TRIGGER_OFFSET,
+                CLIC_CLICINFO_NUM_TRIGGER_MASK,
+            ) as u8,
+        }
+    }
+
+    /// Check whether interrupt ID `int_nr` is implemented.
+    #[inline]
+    pub const fn has_interrupt(&self, int_nr: u16) -> bool {
+        int_nr < self.num_interrupt
+    }
+
+    /// Check whether any interrupt triggers are implemented.
+    #[inline]
+    pub const fn has_triggers(&self) -> bool {
+        self.num_trigger != 0
+    }
+}
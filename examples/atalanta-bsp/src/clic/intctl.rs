@@ -32,57 +32,177 @@ impl INTCTL {
         Self { ptr: address as _ }
     }
 
-    /// Check interrupt level for this interrupt.
     #[inline]
-    pub fn level(self) -> u8 {
+    fn read_ctl(self) -> u8 {
         // SAFETY: valid interrupt number
         let reg: Reg<u32, RW> = unsafe { Reg::new(self.ptr) };
 
-        // TODO: need to figure out how many are actually priority bits and how many are
-        // level bits and mask accordingly
         reg.read_bits(8 * Self::INTCTL_OFFSET, 7 + 8 * Self::INTCTL_OFFSET) as u8
     }
 
-    /// Set interrupt level for this interrupt.
     #[inline]
-    pub fn set_level(self, level: u8) {
+    fn write_ctl(self, ctl: u8) {
         // SAFETY: valid interrupt number
         let reg: Reg<u32, RW> = unsafe { Reg::new(self.ptr) };
 
         reg.write_bits(
             8 * Self::INTCTL_OFFSET,
             7 + 8 * Self::INTCTL_OFFSET,
-            level as _,
+            ctl as _,
         )
     }
 
-    // N.b., 2024-03-11 rt-ss/CLIC does not implement priority bits at all at
-    // this time
-    /*
+    /// Check how many bits of `clicintctl[i]` encode the interrupt level.
+    #[inline]
+    pub fn level_bits(self) -> u32 {
+        CtlLayout::current().level_bits
+    }
+
+    /// Check how many bits of `clicintctl[i]` encode the interrupt priority.
+    #[inline]
+    pub fn priority_bits(self) -> u32 {
+        CtlLayout::current().priority_bits()
+    }
+
+    /// Check the highest interrupt priority supported by the current
+    /// configuration, sa. [INTCTL::set_priority].
+    #[inline]
+    pub fn max_priority(self) -> u8 {
+        CtlLayout::current().max_priority()
+    }
+
+    /// Check interrupt level for this interrupt.
+    ///
+    /// The level is left-justified, i.e., bits that are not part of the
+    /// interrupt level read as 1, as specified by the CLIC spec.
+    #[inline]
+    pub fn level(self) -> u8 {
+        CtlLayout::current().level(self.read_ctl())
+    }
+
+    /// Set interrupt level for this interrupt.
+    ///
+    /// The level is left-justified: only the upper [INTCTL::level_bits] bits of
+    /// `level` are stored, e.g., levels `0x80` and `0xbf` are the same level
+    /// when two level bits are available. The interrupt priority is left as-is.
+    #[inline]
+    pub fn set_level(self, level: u8) {
+        let ctl = CtlLayout::current().with_level(self.read_ctl(), level);
+        self.write_ctl(ctl)
+    }
+
     /// Check interrupt priority for this interrupt.
+    ///
+    /// Returns a value in `0..=`[INTCTL::max_priority].
     #[inline]
     pub fn priority(self) -> u8 {
-        // SAFETY: valid interrupt number
-        let reg: Reg<u32, RW> = unsafe { Reg::new(self.ptr) };
-
-        // TODO: need to figure out how many are actually priority bits and how many are
-        // level bits and mask accordingly
-        reg.read_bits(8 * Self::INTCTL_OFFSET, 7 + 8 * Self::INTCTL_OFFSET) as u8
+        CtlLayout::current().priority(self.read_ctl())
     }
 
     /// Set interrupt priority for this interrupt.
+    ///
+    /// `priority` is a value in `0..=`[INTCTL::max_priority], higher values
+    /// are taken first. The interrupt level is left as-is.
+    ///
+    /// # Errors
+    ///
+    /// Returns [InvalidPriority] if `priority` exceeds [INTCTL::max_priority],
+    /// e.g., because all implemented bits are assigned to the level.
     #[inline]
-    pub fn set_priority(self, priority: u8) {
-        // SAFETY: valid interrupt number
-        let reg: Reg<u32, RW> = unsafe { Reg::new(self.ptr) };
+    pub fn set_priority(self, priority: u8) -> Result<(), InvalidPriority> {
+        let ctl = CtlLayout::current().with_priority(self.read_ctl(), priority)?;
+        self.write_ctl(ctl);
+        Ok(())
+    }
+}
 
-        // TODO: need to figure out how many are actually priority bits and how many are
-        // level bits and mask accordingly
-        reg.write_bits(
-            8 * Self::INTCTL_OFFSET,
-            7 + 8 * Self::INTCTL_OFFSET,
-            priority as _,
-        )
+/// Priority exceeds the number of priority bits in `clicintctl[i]`
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "ufmt", derive(crate::ufmt::derive::uDebug))]
+pub struct InvalidPriority;
+
+/// Split of `clicintctl[i]` into level & priority bits
+///
+/// The upper `level_bits` bits encode the level, the next `ctl_bits -
+/// level_bits` bits the priority. The lower `8 - ctl_bits` bits are not
+/// implemented and hardwired to 1.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) struct CtlLayout {
+    /// Number of bits implemented in `clicintctl[i]`, i.e.,
+    /// `CLICINFO.CLICINTCTLBITS`
+    ctl_bits: u32,
+    /// Number of implemented bits assigned to the level, i.e.,
+    /// `min(mnlbits, CLICINTCTLBITS)`
+    level_bits: u32,
+}
+
+impl CtlLayout {
+    /// Reads the current split from `smclicconfig` and `CLICINFO`
+    #[inline]
+    pub(crate) fn current() -> Self {
+        let ctl_bits = (super::Clic::info().intctlbits as u32).min(8);
+        let level_bits = super::Clic::smclicconfig().mnlbits().min(ctl_bits);
+        Self {
+            ctl_bits,
+            level_bits,
+        }
+    }
+
+    #[inline]
+    const fn priority_bits(self) -> u32 {
+        self.ctl_bits - self.level_bits
+    }
+
+    #[inline]
+    const fn max_priority(self) -> u8 {
+        ((1u32 << self.priority_bits()) - 1) as u8
+    }
+
+    /// Mask of the bits that are hardwired to 1
+    #[inline]
+    const fn unimplemented_mask(self) -> u8 {
+        (0xff >> self.ctl_bits) as u8
+    }
+
+    #[inline]
+    const fn level_mask(self) -> u8 {
+        !((0xff >> self.level_bits) as u8)
+    }
+
+    #[inline]
+    const fn priority_shift(self) -> u32 {
+        8 - self.ctl_bits
+    }
+
+    #[inline]
+    const fn priority_mask(self) -> u8 {
+        !self.level_mask() & !self.unimplemented_mask()
+    }
+
+    #[inline]
+    pub(crate) const fn level(self, ctl: u8) -> u8 {
+        ctl | !self.level_mask()
+    }
+
+    #[inline]
+    pub(crate) const fn priority(self, ctl: u8) -> u8 {
+        ((ctl & self.priority_mask()) as u32 >> self.priority_shift()) as u8
+    }
+
+    /// Returns `ctl` with the level replaced by `level`
+    #[inline]
+    pub(crate) const fn with_level(self, ctl: u8, level: u8) -> u8 {
+        (level & self.level_mask()) | (ctl & self.priority_mask()) | self.unimplemented_mask()
+    }
+
+    /// Returns `ctl` with the priority replaced by `priority`
+    #[inline]
+    pub(crate) fn with_priority(self, ctl: u8, priority: u8) -> Result<u8, InvalidPriority> {
+        if priority > self.max_priority() {
+            return Err(InvalidPriority);
+        }
+        Ok((ctl & self.level_mask())
+            | ((priority as u32) << self.priority_shift()) as u8
+            | self.unimplemented_mask())
     }
-    */
 }
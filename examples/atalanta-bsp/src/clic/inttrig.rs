@@ -1,10 +1,32 @@
 //! CLIC interrupt trigger register.
+use riscv_peripheral::common::{Reg, RW};
+
+use crate::{
+    mmap::{CLIC_INTTRIGG_ENABLE_BIT, CLIC_INTTRIGG_INT_NUMBER_MASK},
+    register::{tdata1, tselect},
+};
+
+use super::InterruptNumber;
+
+/// Action taken by the hart when an interrupt trigger fires
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[repr(u8)]
+pub enum TriggerAction {
+    /// Raise a breakpoint exception
+    Breakpoint = 0,
+    /// Enter Debug Mode, e.g., halt into GDB
+    DebugMode = 1,
+}
 
 /// CLIC interrupt trigger register.
 ///
 /// Optional interrupt triggers (`clicinttrig[i]`) are used to generate a
 /// breakpoint exception, entry into Debug Mode, or a trace action. If these
 /// registers are not implemented, they appear as hard-wired zeros.
+///
+/// An enabled trigger fires when the CLIC takes the selected interrupt. The
+/// action is determined by the hart's debug trigger module, sa.
+/// [Clic::set_trigger](super::Clic::set_trigger).
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 #[repr(transparent)]
 pub struct INTTRIG {
@@ -12,6 +34,8 @@ pub struct INTTRIG {
 }
 
 impl INTTRIG {
+    const INT_NUMBER_BITS: u32 = CLIC_INTTRIGG_INT_NUMBER_MASK.count_ones();
+
     /// Creates a new interrupt trigger register from a base address.
     ///
     /// # Safety
@@ -21,4 +45,85 @@ impl INTTRIG {
     pub(crate) const unsafe fn new(address: usize) -> Self {
         Self { ptr: address as _ }
     }
+
+    /// Check if the trigger is enabled.
+    #[inline]
+    pub fn is_enabled(self) -> bool {
+        // SAFETY: valid interrupt number
+        let reg: Reg<u32, RW> = unsafe { Reg::new(self.ptr) };
+
+        reg.read_bit(CLIC_INTTRIGG_ENABLE_BIT)
+    }
+
+    /// Check which interrupt this trigger fires on.
+    #[inline]
+    pub fn interrupt_number(self) -> u16 {
+        // SAFETY: valid interrupt number
+        let reg: Reg<u32, RW> = unsafe { Reg::new(self.ptr) };
+
+        reg.read_bits(0, Self::INT_NUMBER_BITS as usize - 1) as u16
+    }
+
+    /// Enable the trigger to fire when `int_nr` is taken.
+    #[inline]
+    pub fn enable<I: InterruptNumber>(self, int_nr: I) {
+        // SAFETY: valid interrupt number
+        let reg: Reg<u32, RW> = unsafe { Reg::new(self.ptr) };
+
+        reg.write(
+            (0b1 << CLIC_INTTRIGG_ENABLE_BIT)
+                | (int_nr.number() as u32 & CLIC_INTTRIGG_INT_NUMBER_MASK as u32),
+        )
+    }
+
+    /// Disable the trigger.
+    #[inline]
+    pub fn disable(self) {
+        // SAFETY: valid interrupt number
+        let reg: Reg<u32, RW> = unsafe { Reg::new(self.ptr) };
+
+        reg.clear_bit(CLIC_INTTRIGG_ENABLE_BIT)
+    }
+}
+
+/// Debug trigger module encoding of `tmexttrigger`, i.e., a trigger that
+/// fires on a signal from the interrupt controller.
+///
+/// * `[31:28]` - type = 7
+/// * `[22]` - intctl, fire when the interrupt controller signals a trigger
+/// * `[5:0]` - action
+mod tmexttrigger {
+    pub const TYPE: usize = 7 << 28;
+    pub const TYPE_MASK: usize = 0xf << 28;
+    pub const INTCTL_BIT: usize = 0b1 << 22;
+    pub const ACTION_MASK: usize = 0x3f;
+}
+
+/// Sets up debug trigger `index` to take `action` when the interrupt
+/// controller signals a trigger.
+///
+/// Returns `false` if the debug trigger module has no such trigger, or it
+/// doesn't support `tmexttrigger` or `action`.
+pub(crate) fn arm_debug_trigger(index: usize, action: TriggerAction) -> bool {
+    riscv::interrupt::free(|| unsafe {
+        tselect::write(index);
+        if tselect::read() != index {
+            return false;
+        }
+        tdata1::write(tmexttrigger::TYPE | tmexttrigger::INTCTL_BIT | action as usize);
+        let tdata1 = tdata1::read();
+        tdata1 & tmexttrigger::TYPE_MASK == tmexttrigger::TYPE
+            && tdata1 & tmexttrigger::INTCTL_BIT != 0
+            && tdata1 & tmexttrigger::ACTION_MASK == action as usize
+    })
+}
+
+/// Disables debug trigger `index`.
+pub(crate) fn disarm_debug_trigger(index: usize) {
+    riscv::interrupt::free(|| unsafe {
+        tselect::write(index);
+        if tselect::read() == index {
+            tdata1::write(0);
+        }
+    })
 }
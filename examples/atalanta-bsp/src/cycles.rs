@@ -0,0 +1,90 @@
+//! Cycle-accurate busy waits and timestamps on `mcycle`
+//!
+//! `mcycle` counts CPU clock cycles, so it resolves much finer than the timers
+//! on the peripheral clock and doesn't depend on the optimization level like
+//! [asm_delay](crate::asm_delay). Useful for sub-microsecond waits in interrupt
+//! handlers.
+//!
+//! `mcycle` must not be inhibited, sa. `mcountinhibit`.
+use core::ops::{Add, Sub};
+
+use embedded_hal::delay::DelayNs;
+
+use crate::{mtimer::Duration, register::mcycle, CPU_FREQ};
+
+/// Busy-waits for at least `n` CPU clock cycles
+#[inline]
+pub fn delay_cycles(n: u64) {
+    let start = CycleInstant::now();
+    while start.elapsed_cycles() < n {}
+}
+
+/// Point in time as read from `mcycle`
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd)]
+#[cfg_attr(feature = "ufmt", derive(crate::ufmt::derive::uDebug))]
+pub struct CycleInstant(u64);
+
+impl CycleInstant {
+    /// Returns the current cycle count
+    #[inline]
+    pub fn now() -> Self {
+        Self(mcycle::read64())
+    }
+
+    /// Returns the raw cycle count
+    #[inline]
+    pub const fn cycles(&self) -> u64 {
+        self.0
+    }
+
+    /// Returns the number of cycles since `earlier`, or zero if `earlier` is
+    /// later than `self`
+    #[inline]
+    pub const fn cycles_since(&self, earlier: CycleInstant) -> u64 {
+        self.0.saturating_sub(earlier.0)
+    }
+
+    /// Returns the number of cycles passed since `self`
+    #[inline]
+    pub fn elapsed_cycles(&self) -> u64 {
+        Self::now().cycles_since(*self)
+    }
+
+    /// Returns the time passed since `self`
+    ///
+    /// [Duration] is measured in CPU clock cycles, so no precision is lost.
+    #[inline]
+    pub fn elapsed(&self) -> Duration {
+        Duration::from_ticks(self.elapsed_cycles())
+    }
+}
+
+impl Add<u64> for CycleInstant {
+    type Output = Self;
+
+    #[inline]
+    fn add(self, cycles: u64) -> Self {
+        Self(self.0 + cycles)
+    }
+}
+
+impl Sub for CycleInstant {
+    type Output = u64;
+
+    /// Returns the number of cycles between the instants, sa.
+    /// [CycleInstant::cycles_since]
+    #[inline]
+    fn sub(self, earlier: Self) -> u64 {
+        self.cycles_since(earlier)
+    }
+}
+
+/// Busy-waits on `mcycle`
+pub struct CycleDelay;
+
+impl DelayNs for CycleDelay {
+    #[inline]
+    fn delay_ns(&mut self, ns: u32) {
+        delay_cycles((ns as u64 * CPU_FREQ as u64).div_ceil(1_000_000_000));
+    }
+}
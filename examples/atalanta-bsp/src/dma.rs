@@ -0,0 +1,267 @@
+//! Driver for [NanoDMA](https://github.com/ANurmi/NanoDMA)
+//!
+//! NanoDMA moves 32-bit words between any two memory mapped regions, e.g.,
+//! DMEM and SRAM. The end of a transfer is signaled by a pulse on
+//! [Interrupt::Dma0], which is latched by the CLIC as an edge-triggered
+//! interrupt. Completion can be observed either by polling
+//! ([Transfer::is_done], [Transfer::wait]), by awaiting the [Transfer], or by
+//! servicing the interrupt and calling [on_interrupt] from the handler.
+use core::{
+    future::Future,
+    marker::PhantomData,
+    pin::Pin,
+    ptr,
+    sync::atomic::{AtomicBool, Ordering},
+    task::{Context, Poll, Waker},
+};
+
+use crate::{
+    clic::{Clic, Polarity, Trig},
+    mask_u32p,
+    mmap::dma::*,
+    write_u32p, Interrupt,
+};
+
+/// Set by [on_interrupt] when the interrupt handler observes a completed
+/// transfer
+static DONE: AtomicBool = AtomicBool::new(false);
+
+/// Set while a transfer is in flight, including a leaked one
+static BUSY: AtomicBool = AtomicBool::new(false);
+
+/// Waker of the task awaiting the current [Transfer], if any
+static mut WAKER: Option<Waker> = None;
+
+/// Maximum length of a single transfer in bytes
+pub const MAX_TRANSFER_LEN: usize = DMA_CTRL_LEN_MASK as usize * 4;
+
+/// The interrupt line raised on transfer completion
+pub const DMA_IRQ: Interrupt = Interrupt::Dma0;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DmaError {
+    /// Source or destination address is not 4-byte aligned
+    Unaligned,
+    /// Length is zero, not a multiple of 4 or exceeds [MAX_TRANSFER_LEN]
+    InvalidLength,
+}
+
+/// Driver for NanoDMA
+pub struct Dma {}
+
+impl Dma {
+    /// Initializes the DMA owned by `dma`, sa. [Dma::init]
+    #[inline]
+    pub fn new(_dma: crate::peripherals::DMA) -> Self {
+        // SAFETY: the token proves ownership of the DMA
+        unsafe { Self::init() }
+    }
+
+    /// Initializes the DMA and configures [DMA_IRQ] as a positive
+    /// edge-triggered interrupt so that completion is latched even when the
+    /// interrupt is not enabled
+    ///
+    /// # Safety
+    ///
+    /// * Bypasses the ownership tokens of [Peripherals](crate::Peripherals). No
+    ///   other DMA driver may be in use. Prefer [Dma::new].
+    #[inline]
+    pub unsafe fn init() -> Self {
+        Clic::attr(DMA_IRQ).set_trig(Trig::Edge);
+        Clic::attr(DMA_IRQ).set_polarity(Polarity::Pos);
+        // SAFETY: DMA interrupt is an edge-triggered line, software writes are
+        // supported
+        unsafe { Clic::ip(DMA_IRQ).unpend() };
+        DONE.store(false, Ordering::Relaxed);
+        BUSY.store(false, Ordering::Relaxed);
+        Self {}
+    }
+
+    /// # Safety
+    ///
+    /// Returns a potentially uninitialized instance of the DMA. Make sure to
+    /// call [Dma::init] prior to this call and that no other transfer is in
+    /// flight.
+    #[inline]
+    pub const unsafe fn instance() -> Self {
+        Self {}
+    }
+
+    /// Raise [DMA_IRQ] with interrupt level `level` on transfer completion
+    ///
+    /// Call [on_interrupt] from the `Dma0` interrupt handler to let pending
+    /// transfers know that they have completed.
+    ///
+    /// # Safety
+    ///
+    /// * Enabling an interrupt source can break mask-based critical sections.
+    #[inline]
+    pub unsafe fn listen(&mut self, level: u8) {
+        Clic::attr(DMA_IRQ).set_shv(true);
+        Clic::ctl(DMA_IRQ).set_level(level);
+        Clic::ie(DMA_IRQ).enable();
+    }
+
+    /// Stop raising [DMA_IRQ] on transfer completion
+    #[inline]
+    pub fn unlisten(&mut self) {
+        Clic::ie(DMA_IRQ).disable();
+    }
+
+    /// Starts a transfer of `len` bytes from `src` to `dst`
+    ///
+    /// Both addresses must be 4-byte aligned and `len` must be a non-zero
+    /// multiple of 4 no greater than [MAX_TRANSFER_LEN]. Blocks until a
+    /// previous transfer, e.g., a leaked one, has completed.
+    ///
+    /// # Safety
+    ///
+    /// * `src..src + len` must be valid for reads and `dst..dst + len` must be
+    ///   valid for writes until the returned [Transfer] completes.
+    /// * Neither region may be accessed by software while the transfer is in
+    ///   flight.
+    #[inline]
+    pub unsafe fn start(
+        &mut self,
+        src: usize,
+        dst: usize,
+        len: usize,
+    ) -> Result<Transfer<'_>, DmaError> {
+        self.start_with(src, dst, len, ())
+    }
+
+    /// [Dma::start] with `buf` owned by the returned [Transfer]
+    unsafe fn start_with<B>(
+        &mut self,
+        src: usize,
+        dst: usize,
+        len: usize,
+        buf: B,
+    ) -> Result<Transfer<'_, B>, DmaError> {
+        if src % 4 != 0 || dst % 4 != 0 {
+            return Err(DmaError::Unaligned);
+        }
+        if len == 0 || len % 4 != 0 || len > MAX_TRANSFER_LEN {
+            return Err(DmaError::InvalidLength);
+        }
+
+        // The hardware has no busy flag, so the completion of the previous
+        // transfer is the only way to know that it's idle
+        while BUSY.load(Ordering::Relaxed) && !poll_done() {}
+
+        DONE.store(false, Ordering::Relaxed);
+        BUSY.store(true, Ordering::Relaxed);
+        // SAFETY: DMA interrupt is an edge-triggered line, software writes are
+        // supported
+        Clic::ip(DMA_IRQ).unpend();
+
+        write_u32p(ptr::addr_of_mut!((*DMA).ctrl), (len / 4) as u32);
+        write_u32p(ptr::addr_of_mut!((*DMA).src), src as u32);
+        write_u32p(ptr::addr_of_mut!((*DMA).dst), dst as u32);
+        mask_u32p(ptr::addr_of_mut!((*DMA).ctrl), DMA_CTRL_START_BIT);
+
+        Ok(Transfer {
+            buf: Some(buf),
+            _dma: PhantomData,
+        })
+    }
+
+    /// Copies the contents of `src` into `dst`
+    ///
+    /// Both buffers must be of equal length. The buffers are handed back by
+    /// [Transfer::wait] or by awaiting the [Transfer] once the hardware is
+    /// done. Leaking the [Transfer] leaks the buffers with it, so the hardware
+    /// never writes memory that is accessible to software.
+    #[inline]
+    pub fn copy(
+        &mut self,
+        src: &'static [u32],
+        dst: &'static mut [u32],
+    ) -> Result<Transfer<'_, (&'static [u32], &'static mut [u32])>, DmaError> {
+        if src.len() != dst.len() {
+            return Err(DmaError::InvalidLength);
+        }
+        let (src_addr, dst_addr) = (src.as_ptr() as usize, dst.as_mut_ptr() as usize);
+        let len = core::mem::size_of_val(src);
+        // SAFETY: both buffers are `'static` and owned by the transfer until the
+        // hardware is done
+        unsafe { self.start_with(src_addr, dst_addr, len, (src, dst)) }
+    }
+}
+
+/// Returns `true` when the hardware has signaled completion of the current
+/// transfer
+fn poll_done() -> bool {
+    if !DONE.load(Ordering::Relaxed) {
+        // Interrupt not serviced, check the latched completion pulse instead
+        // SAFETY: DMA_IRQ is configured as edge-triggered in `Dma::init`
+        if !unsafe { Clic::ip(DMA_IRQ).is_pending() } {
+            return false;
+        }
+        unsafe { Clic::ip(DMA_IRQ).unpend() };
+        DONE.store(true, Ordering::Relaxed);
+    }
+    BUSY.store(false, Ordering::Relaxed);
+    true
+}
+
+/// An in-flight DMA transfer
+///
+/// `B` holds the buffers owned by the transfer, if any. Dropping the transfer
+/// blocks until the hardware reports completion.
+#[must_use = "dropping a transfer blocks until it completes"]
+pub struct Transfer<'a, B = ()> {
+    buf: Option<B>,
+    _dma: PhantomData<&'a mut Dma>,
+}
+
+impl<B> Transfer<'_, B> {
+    /// Returns `true` when the hardware has signaled completion
+    #[inline]
+    pub fn is_done(&self) -> bool {
+        poll_done()
+    }
+
+    /// Blocks until the transfer has completed and returns the buffers
+    #[inline]
+    pub fn wait(mut self) -> B {
+        while !self.is_done() {}
+        self.buf.take().unwrap()
+    }
+}
+
+impl<B: Unpin> Future for Transfer<'_, B> {
+    type Output = B;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // Register the waker before checking to avoid missing a completion that
+        // arrives in between
+        riscv::interrupt::free(|| unsafe { WAKER = Some(cx.waker().clone()) });
+
+        if self.is_done() {
+            Poll::Ready(self.get_mut().buf.take().expect("polled after completion"))
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+impl<B> Drop for Transfer<'_, B> {
+    fn drop(&mut self) {
+        while !self.is_done() {}
+        riscv::interrupt::free(|| unsafe { WAKER = None });
+    }
+}
+
+/// Signals transfer completion to the pending [Transfer]
+///
+/// Call this from the `Dma0` interrupt handler when using [Dma::listen].
+#[inline]
+pub fn on_interrupt() {
+    DONE.store(true, Ordering::Relaxed);
+    // SAFETY: interrupts are disabled in the interrupt handler, no other access to
+    // WAKER can be in progress
+    if let Some(waker) = unsafe { (*ptr::addr_of_mut!(WAKER)).take() } {
+        waker.wake();
+    }
+}
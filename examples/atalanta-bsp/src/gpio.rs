@@ -1,13 +1,35 @@
+//! Implementation of [PULP APB GPIO](https://github.com/pulp-platform/apb_gpio/) (v0.2.0)
+//!
+//! Two APIs are provided:
+//!
+//! * [GpioHal] operates on a bank of 32 pins using bitmasks, and
+//! * [Pin] is a typestate pin split from the [Gpio] singleton that implements
+//!   the [embedded_hal::digital] traits.
+//!
+//! Input pins can raise [Interrupt::Gpio] on edges, sa. [Pin::listen]. A
+//! single `Gpio` interrupt handler can fan out to per-pin handlers by calling
+//! [dispatch].
+use core::{convert::Infallible, marker::PhantomData, ptr};
+
+use embedded_hal::digital::{self, InputPin, OutputPin, StatefulOutputPin};
+
 use crate::{
-    mask_u32,
-    mmap::gpio::{RegisterBlock, GPIO_BASE},
-    toggle_u32, unmask_u32,
+    clic::{Clic, Polarity, Trig},
+    mask_u32p,
+    mmap::gpio::{PadRegisterBlock, RegisterBlock, GPIO_BASE},
+    modify_u32,
+    peripherals::{GPIO_HI, GPIO_LO},
+    read_u32p, toggle_u32, unmask_u32p, write_u32p, Interrupt,
 };
-use core::mem;
 
 pub const GPIO_LO_BASE: usize = GPIO_BASE;
-pub const GPIO_HI_BASE: usize = GPIO_BASE + 0x38;
+pub const GPIO_HI_BASE: usize = GPIO_BASE + core::mem::size_of::<PadRegisterBlock>();
 
+/// Bitmask driver for a bank of 32 GPIO pins
+///
+/// The type parameter represents the base address for the bank. The driver
+/// takes no ownership, hence its functions are `unsafe`; prefer [Pin]s split
+/// from [Gpio].
 pub struct GpioHal<const BASE_ADDR: usize>;
 
 /// Pins 0..=31, sa. [GpioHal]
@@ -16,51 +38,441 @@ pub type GpioLo = GpioHal<GPIO_LO_BASE>;
 pub type GpioHi = GpioHal<GPIO_HI_BASE>;
 
 impl<const BASE_ADDR: usize> GpioHal<BASE_ADDR> {
-    /// Enable clocks
-    pub fn en(mask: u32) {
-        let gpio = GPIO_BASE as *mut RegisterBlock;
+    const PAD: *mut PadRegisterBlock = BASE_ADDR as *mut _;
 
-        // Enable clocks for gpios 0..=3
-        mask_u32(unsafe { mem::transmute(&mut (*gpio).pads[0].en) }, mask);
+    /// Enable clocks
+    ///
+    /// # Safety
+    ///
+    /// * Pins in `mask` must not be owned by a [Pin].
+    pub unsafe fn en(mask: u32) {
+        mask_u32p(unsafe { ptr::addr_of_mut!((*Self::PAD).en) }, mask);
     }
 
     /// Set GPIOs as output
-    pub fn set_output(mask: u32) {
-        let gpio = GPIO_BASE as *mut RegisterBlock;
-
+    ///
+    /// # Safety
+    ///
+    /// * Pins in `mask` must not be owned by a [Pin].
+    pub unsafe fn set_output(mask: u32) {
         // Set DIR = 1 (output)
-        mask_u32(unsafe { mem::transmute(&mut (*gpio).pads[0].dir) }, mask);
+        mask_u32p(unsafe { ptr::addr_of_mut!((*Self::PAD).dir) }, mask);
     }
 
     /// Set GPIOs as input
-    pub fn set_input(mask: u32) {
-        let gpio = GPIO_BASE as *mut RegisterBlock;
-
+    ///
+    /// # Safety
+    ///
+    /// * Pins in `mask` must not be owned by a [Pin].
+    pub unsafe fn set_input(mask: u32) {
         // Set DIR = 0 (input)
-        unmask_u32(unsafe { mem::transmute(&mut (*gpio).pads[0].dir) }, mask);
+        unmask_u32p(unsafe { ptr::addr_of_mut!((*Self::PAD).dir) }, mask);
+    }
+
+    /// Drive GPIOs high
+    ///
+    /// # Safety
+    ///
+    /// * Pins in `mask` must not be owned by a [Pin].
+    pub unsafe fn set_high(mask: u32) {
+        mask_u32p(unsafe { ptr::addr_of_mut!((*Self::PAD).data_out) }, mask);
+    }
+
+    /// Drive GPIOs low
+    ///
+    /// # Safety
+    ///
+    /// * Pins in `mask` must not be owned by a [Pin].
+    pub unsafe fn set_low(mask: u32) {
+        unmask_u32p(unsafe { ptr::addr_of_mut!((*Self::PAD).data_out) }, mask);
     }
 
-    pub fn set_high(mask: u32) {
-        let gpio = GPIO_BASE as *mut RegisterBlock;
-        mask_u32(
-            unsafe { mem::transmute(&mut (*gpio).pads[0].data_out) },
+    /// Toggle GPIO outputs
+    ///
+    /// # Safety
+    ///
+    /// * Pins in `mask` must not be owned by a [Pin].
+    pub unsafe fn toggle(mask: u32) {
+        toggle_u32(
+            unsafe { ptr::addr_of_mut!((*Self::PAD).data_out) } as usize,
             mask,
         );
     }
+}
 
-    pub fn set_low(mask: u32) {
-        let gpio = GPIO_BASE as *mut RegisterBlock;
-        unmask_u32(
-            unsafe { mem::transmute(&mut (*gpio).pads[0].data_out) },
-            mask,
+/// Edge that raises a GPIO interrupt
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum Edge {
+    Falling = 0b00,
+    Rising = 0b01,
+    Both = 0b10,
+}
+
+/// Pull resistor configuration of a pad
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[repr(u8)]
+pub enum Pull {
+    Disabled = 0b0,
+    Enabled = 0b1,
+}
+
+/// Drive strength configuration of a pad
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[repr(u8)]
+pub enum DriveStrength {
+    Low = 0b0,
+    High = 0b1,
+}
+
+/// Pad configuration of a single pin, sa. [PadRegisterBlock::pad_cfg]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct PadConfig {
+    pub pull: Pull,
+    pub drive: DriveStrength,
+}
+
+impl PadConfig {
+    const PULL_BIT_IDX: u32 = 0;
+    const DRIVE_BIT_IDX: u32 = 4;
+    const FIELD_MASK: u8 = 0b11;
+
+    /// Decodes the configuration byte of a pin
+    #[inline]
+    pub fn from_bits(bits: u8) -> Self {
+        Self {
+            pull: match (bits >> Self::PULL_BIT_IDX) & Self::FIELD_MASK {
+                0 => Pull::Disabled,
+                _ => Pull::Enabled,
+            },
+            drive: match (bits >> Self::DRIVE_BIT_IDX) & Self::FIELD_MASK {
+                0 => DriveStrength::Low,
+                _ => DriveStrength::High,
+            },
+        }
+    }
+
+    /// Encodes the configuration byte of a pin
+    #[inline]
+    pub fn bits(&self) -> u8 {
+        ((self.pull as u8) << Self::PULL_BIT_IDX) | ((self.drive as u8) << Self::DRIVE_BIT_IDX)
+    }
+}
+
+impl Default for PadConfig {
+    fn default() -> Self {
+        Self {
+            pull: Pull::Disabled,
+            drive: DriveStrength::Low,
+        }
+    }
+}
+
+/// Typestate for a pin whose hardware configuration is unknown, sa.
+/// [Gpio::split]
+pub struct Unconfigured;
+/// Typestate for a pin configured as input
+pub struct Input;
+/// Typestate for a pin configured as output
+pub struct Output;
+
+/// A single GPIO pin `N` in mode `MODE` ([Unconfigured], [Input] or [Output])
+///
+/// Pins 0..=31 are located in the low bank and pins 32..=63 in the high bank.
+pub struct Pin<const N: usize, MODE> {
+    _mode: PhantomData<MODE>,
+}
+
+impl<const N: usize, MODE> Pin<N, MODE> {
+    const PAD: *mut PadRegisterBlock =
+        (GPIO_BASE + (N / 32) * core::mem::size_of::<PadRegisterBlock>()) as *mut _;
+    const MASK: u32 = 0b1 << (N % 32);
+
+    #[inline]
+    const fn new() -> Self {
+        Self { _mode: PhantomData }
+    }
+
+    /// Returns the pin number
+    #[inline]
+    pub const fn number(&self) -> usize {
+        N
+    }
+
+    /// Configures the pin as an input
+    #[inline]
+    pub fn into_input(self) -> Pin<N, Input> {
+        // Clock must be enabled for a GPIO configured in input mode
+        mask_u32p(unsafe { ptr::addr_of_mut!((*Self::PAD).en) }, Self::MASK);
+        unmask_u32p(unsafe { ptr::addr_of_mut!((*Self::PAD).dir) }, Self::MASK);
+        Pin::new()
+    }
+
+    /// Configures the pin as an output
+    #[inline]
+    pub fn into_output(self) -> Pin<N, Output> {
+        mask_u32p(unsafe { ptr::addr_of_mut!((*Self::PAD).en) }, Self::MASK);
+        mask_u32p(unsafe { ptr::addr_of_mut!((*Self::PAD).dir) }, Self::MASK);
+        Pin::new()
+    }
+
+    /// Returns the pull & drive strength configuration of the pad
+    #[inline]
+    pub fn pad_config(&self) -> PadConfig {
+        let bit = N % 32;
+        let cfg = read_u32p(unsafe { ptr::addr_of!((*Self::PAD).pad_cfg[bit / 4]) });
+        PadConfig::from_bits((cfg >> (8 * (bit % 4))) as u8)
+    }
+
+    /// Sets the pull & drive strength configuration of the pad
+    #[inline]
+    pub fn set_pad_config(&mut self, cfg: PadConfig) {
+        let bit = N % 32;
+        modify_u32(
+            unsafe { ptr::addr_of_mut!((*Self::PAD).pad_cfg[bit / 4]) } as usize,
+            cfg.bits() as u32,
+            0xff,
+            8 * (bit % 4),
         );
     }
+}
 
-    pub fn toggle(mask: u32) {
-        let gpio = GPIO_BASE as *mut RegisterBlock;
-        toggle_u32(
-            unsafe { mem::transmute(&mut (*gpio).pads[0].data_out) },
-            mask,
+impl<const N: usize> Pin<N, Input> {
+    /// Raise [Interrupt::Gpio] on `edge`
+    ///
+    /// The interrupt line must also be enabled on the CLIC, sa.
+    /// [enable_interrupt].
+    #[inline]
+    pub fn listen(&mut self, edge: Edge) {
+        let bit = N % 32;
+        modify_u32(
+            unsafe { ptr::addr_of_mut!((*Self::PAD).int_type[bit / 16]) } as usize,
+            edge as u32,
+            0b11,
+            2 * (bit % 16),
+        );
+        mask_u32p(
+            unsafe { ptr::addr_of_mut!((*Self::PAD).int_en) },
+            Self::MASK,
         );
     }
+
+    /// Stop raising [Interrupt::Gpio] for this pin
+    #[inline]
+    pub fn unlisten(&mut self) {
+        unmask_u32p(
+            unsafe { ptr::addr_of_mut!((*Self::PAD).int_en) },
+            Self::MASK,
+        );
+    }
+}
+
+impl<const N: usize> digital::ErrorType for Pin<N, Input> {
+    type Error = Infallible;
+}
+
+impl<const N: usize> digital::ErrorType for Pin<N, Output> {
+    type Error = Infallible;
+}
+
+impl<const N: usize> InputPin for Pin<N, Input> {
+    #[inline]
+    fn is_high(&mut self) -> Result<bool, Self::Error> {
+        Ok(read_u32p(unsafe { ptr::addr_of!((*Self::PAD).data_in) }) & Self::MASK != 0)
+    }
+
+    #[inline]
+    fn is_low(&mut self) -> Result<bool, Self::Error> {
+        self.is_high().map(|high| !high)
+    }
+}
+
+impl<const N: usize> OutputPin for Pin<N, Output> {
+    #[inline]
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        write_u32p(
+            unsafe { ptr::addr_of_mut!((*Self::PAD).out_set) },
+            Self::MASK,
+        );
+        Ok(())
+    }
+
+    #[inline]
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        write_u32p(
+            unsafe { ptr::addr_of_mut!((*Self::PAD).out_clr) },
+            Self::MASK,
+        );
+        Ok(())
+    }
+}
+
+impl<const N: usize> StatefulOutputPin for Pin<N, Output> {
+    #[inline]
+    fn is_set_high(&mut self) -> Result<bool, Self::Error> {
+        Ok(read_u32p(unsafe { ptr::addr_of!((*Self::PAD).data_out) }) & Self::MASK != 0)
+    }
+
+    #[inline]
+    fn is_set_low(&mut self) -> Result<bool, Self::Error> {
+        self.is_set_high().map(|high| !high)
+    }
+}
+
+/// GPIO peripheral singleton
+pub struct Gpio {
+    _private: (),
+}
+
+impl Gpio {
+    /// Returns the GPIO peripheral owned by `lo` & `hi`
+    #[inline]
+    pub fn new(_lo: GPIO_LO, _hi: GPIO_HI) -> Self {
+        Self { _private: () }
+    }
+
+    /// # Safety
+    ///
+    /// Returns the GPIO peripheral regardless of whether it's already owned
+    /// elsewhere, allowing aliased access to the pins. Prefer [Gpio::new].
+    #[inline]
+    pub unsafe fn steal() -> Self {
+        Self { _private: () }
+    }
+
+    /// Splits the peripheral into individual pins, all [Unconfigured]
+    ///
+    /// This does not touch the hardware. Call [Pin::into_input] or
+    /// [Pin::into_output] to configure a pin before use.
+    #[inline]
+    pub fn split(self) -> Pins {
+        Pins::new()
+    }
+}
+
+macro_rules! pins {
+    ($($name:ident: $n:literal),* $(,)?) => {
+        /// All GPIO pins, sa. [Gpio::split]
+        pub struct Pins {
+            $(
+                pub $name: Pin<$n, Unconfigured>,
+            )*
+        }
+
+        impl Pins {
+            #[inline]
+            const fn new() -> Self {
+                Self {
+                    $(
+                        $name: Pin::new(),
+                    )*
+                }
+            }
+        }
+    };
+}
+
+pins!(
+    p0: 0, p1: 1, p2: 2, p3: 3, p4: 4, p5: 5, p6: 6, p7: 7,
+    p8: 8, p9: 9, p10: 10, p11: 11, p12: 12, p13: 13, p14: 14, p15: 15,
+    p16: 16, p17: 17, p18: 18, p19: 19, p20: 20, p21: 21, p22: 22, p23: 23,
+    p24: 24, p25: 25, p26: 26, p27: 27, p28: 28, p29: 29, p30: 30, p31: 31,
+    p32: 32, p33: 33, p34: 34, p35: 35, p36: 36, p37: 37, p38: 38, p39: 39,
+    p40: 40, p41: 41, p42: 42, p43: 43, p44: 44, p45: 45, p46: 46, p47: 47,
+    p48: 48, p49: 49, p50: 50, p51: 51, p52: 52, p53: 53, p54: 54, p55: 55,
+    p56: 56, p57: 57, p58: 58, p59: 59, p60: 60, p61: 61, p62: 62, p63: 63,
+);
+
+/// Per-pin handlers called by [dispatch]
+static mut HANDLERS: [Option<fn()>; 64] = [None; 64];
+
+/// Registers `handler` to be called by [dispatch] when `pin` has raised an
+/// interrupt. Pass `None` to remove the handler.
+#[inline]
+pub fn set_handler<const N: usize>(_pin: &Pin<N, Input>, handler: Option<fn()>) {
+    riscv::interrupt::free(|| unsafe { (*ptr::addr_of_mut!(HANDLERS))[N] = handler });
+}
+
+/// Enables [Interrupt::Gpio] on the CLIC with interrupt level `level`
+///
+/// The GPIO interrupt line is held high until the interrupt status is read,
+/// i.e., the line is level-triggered.
+///
+/// # Safety
+///
+/// * Enabling an interrupt source can break mask-based critical sections.
+#[inline]
+pub unsafe fn enable_interrupt(level: u8) {
+    Clic::configure(Interrupt::Gpio)
+        .trigger(Trig::Level)
+        .polarity(Polarity::Pos)
+        .shv(true)
+        .level(level)
+        .enable()
+        .keep();
+}
+
+/// Disables [Interrupt::Gpio] on the CLIC
+#[inline]
+pub fn disable_interrupt() {
+    Clic::ie(Interrupt::Gpio).disable();
+}
+
+/// Pins that have raised an interrupt, sa. [take_pending]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct PendingPins(u64);
+
+impl PendingPins {
+    /// Returns the status as a bitmask, bit `i` corresponding to pin `i`
+    #[inline]
+    pub fn bits(&self) -> u64 {
+        self.0
+    }
+
+    /// Returns `true` if `pin` has raised an interrupt
+    #[inline]
+    pub fn is_pending<const N: usize, MODE>(&self, _pin: &Pin<N, MODE>) -> bool {
+        self.0 & (0b1 << N) != 0
+    }
+}
+
+impl Iterator for PendingPins {
+    type Item = usize;
+
+    /// Yields the numbers of the pins that have raised an interrupt
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.0 == 0 {
+            return None;
+        }
+        let pin = self.0.trailing_zeros() as usize;
+        self.0 &= self.0 - 1;
+        Some(pin)
+    }
+}
+
+/// Reads and clears the interrupt status of both banks
+///
+/// Reading the status also lowers the GPIO interrupt line, so call this once
+/// per [Interrupt::Gpio] and act on all of the returned pins.
+#[inline]
+pub fn take_pending() -> PendingPins {
+    let gpio = GPIO_BASE as *mut RegisterBlock;
+    let lo = read_u32p(unsafe { ptr::addr_of!((*gpio).pads[0].int_status) });
+    let hi = read_u32p(unsafe { ptr::addr_of!((*gpio).pads[1].int_status) });
+    PendingPins(((hi as u64) << 32) | lo as u64)
+}
+
+/// Calls the registered handler of each pin that has raised an interrupt
+///
+/// Call this from the `Gpio` interrupt handler. Sa. [set_handler].
+#[inline]
+pub fn dispatch() {
+    for pin in take_pending() {
+        // SAFETY: handlers are only modified in a critical section and this is
+        // called from the interrupt handler
+        if let Some(handler) = unsafe { (*ptr::addr_of!(HANDLERS))[pin] } {
+            handler();
+        }
+    }
 }
@@ -1,92 +1,58 @@
-use core::arch::asm;
-
-use crate::clic::InterruptNumber;
-
-#[derive(Clone, Copy, PartialEq)]
-#[repr(u16)]
-#[cfg_attr(not(feature = "ufmt"), derive(Debug))]
-pub enum Interrupt {
-    // SupervisorSoft = 1,
-    MachineSoft = 3,
-    // SupervisorTimer = 5,
-    MachineTimer = 7,
-    // SupervisorExternal = 9,
-    MachineExternal = 11,
-    /// UART interrupt (Non-standard, overrides S-mode software interrupt
-    /// mapping.)
-    Uart = 17,
-    Gpio = 18,
-    SpiRxTxIrq = 19,
-    /// SPI end of transmission
-    SpiEotIrq = 20,
-    /// Timer 0 overflow
-    Timer0Ovf = 21,
-    /// Timer 0 compare
-    Timer0Cmp = 22,
-    /// Timer1 overflow
-    Timer1Ovf = 23,
-    /// Timer1 compare
-    Timer1Cmp = 24,
-    /// Timer2 overflow
-    Timer2Ovf = 25,
-    /// Timer2 compare
-    Timer2Cmp = 26,
-    /// Timer3 overflow
-    Timer3Ovf = 27,
-    /// Timer3 compare
-    Timer3Cmp = 28,
-    /// Non-maskable interrupt, carried over from standard Ibex
-    Nmi = 31,
-    Dma0 = 32,
-    Dma1 = 33,
-    Dma2 = 34,
-    Dma3 = 35,
-    Dma4 = 36,
-    Dma5 = 37,
-    Dma6 = 38,
-    Dma7 = 39,
-    Dma8 = 40,
-    Dma9 = 41,
-    Dma10 = 42,
-    Dma11 = 43,
-    Dma12 = 44,
-    Dma13 = 45,
-    Dma14 = 46,
-    Dma15 = 47,
-}
+//! Interrupt numbers and interrupt-level based critical sections
+use core::{
+    arch::asm,
+    sync::atomic::{AtomicU8, Ordering},
+};
 
-unsafe impl InterruptNumber for Interrupt {
-    const MAX_INTERRUPT_NUMBER: u16 = 255;
+use crate::{clic::InterruptNumber, register::mintthresh};
 
-    fn number(self) -> u16 {
-        self as u16
-    }
+/// Generates [Interrupt] from `interrupt_table.rs`
+macro_rules! interrupt_table {
+    ($($(#[$meta:meta])* $name:ident = $num:literal,)*) => {
+        #[derive(Clone, Copy, PartialEq)]
+        #[repr(u16)]
+        pub enum Interrupt {
+            $(
+                $(#[$meta])*
+                $name = $num,
+            )*
+        }
+
+        impl Interrupt {
+            /// All interrupts in ascending order of interrupt number
+            pub const ALL: &'static [Interrupt] = &[$(Interrupt::$name,)*];
 
-    fn from_number(value: u16) -> Result<Self, u16> {
-        match value {
-            3 => Ok(Self::MachineSoft),
-            7 => Ok(Self::MachineTimer),
-            11 => Ok(Self::MachineExternal),
-            17 => Ok(Self::Uart),
-            32 => Ok(Self::Dma0),
-            33 => Ok(Self::Dma1),
-            34 => Ok(Self::Dma2),
-            35 => Ok(Self::Dma3),
-            36 => Ok(Self::Dma4),
-            37 => Ok(Self::Dma5),
-            38 => Ok(Self::Dma6),
-            39 => Ok(Self::Dma7),
-            40 => Ok(Self::Dma8),
-            41 => Ok(Self::Dma9),
-            42 => Ok(Self::Dma10),
-            43 => Ok(Self::Dma11),
-            44 => Ok(Self::Dma12),
-            45 => Ok(Self::Dma13),
-            46 => Ok(Self::Dma14),
-            47 => Ok(Self::Dma15),
-
-            _ => Err(value),
+            /// Returns the name of the interrupt, e.g., `"Uart"`
+            pub const fn name(self) -> &'static str {
+                match self {
+                    $(Interrupt::$name => stringify!($name),)*
+                }
+            }
         }
+
+        unsafe impl InterruptNumber for Interrupt {
+            const MAX_INTERRUPT_NUMBER: u16 = 255;
+
+            fn number(self) -> u16 {
+                self as u16
+            }
+
+            fn from_number(value: u16) -> Result<Self, u16> {
+                match value {
+                    $($num => Ok(Self::$name),)*
+                    _ => Err(value),
+                }
+            }
+        }
+    };
+}
+
+include!("interrupt_table.rs");
+
+#[cfg(not(feature = "ufmt"))]
+impl core::fmt::Debug for Interrupt {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(self.name())
     }
 }
 
@@ -129,3 +95,98 @@ where
 
     r
 }
+
+/// Raises the interrupt threshold to `level` while executing `f`, then
+/// restores the previous threshold
+///
+/// Interrupts at or below `level` are held off for the duration of `f`, while
+/// higher-level interrupts keep preempting. This makes for a critical section
+/// among a subset of levels, i.e., `f` may share data with handlers at or below
+/// `level`. The threshold is never lowered: if it's already at or above
+/// `level`, `f` is run as-is.
+///
+/// `level` is an 8-bit interrupt level, sa.
+/// [INTCTL::set_level](crate::clic::intctl::INTCTL::set_level).
+#[inline]
+pub fn with_threshold<F, R>(level: u8, f: F) -> R
+where
+    F: FnOnce() -> R,
+{
+    let prev = raise_threshold(level);
+    let r = f();
+    restore_threshold(prev);
+    r
+}
+
+/// Raises the interrupt threshold to `level`, if it's not already higher,
+/// returning the previous threshold
+///
+/// The threshold is restored by interrupt handlers before they return, so it's
+/// fine for one to preempt between the read and the write.
+#[inline]
+pub(crate) fn raise_threshold(level: u8) -> u8 {
+    let prev = mintthresh::read().bits() as u8;
+    if level > prev {
+        // SAFETY: the threshold is only raised
+        unsafe { mintthresh::write((level as usize).into()) };
+    }
+    prev
+}
+
+/// Restores the interrupt threshold returned by [raise_threshold]
+///
+/// Callers must restore thresholds in the reverse order they were raised, so
+/// that the threshold never drops below a critical section that is still held.
+#[inline]
+pub(crate) fn restore_threshold(prev: u8) {
+    // SAFETY: the critical section that raised the threshold has ended
+    unsafe { mintthresh::write((prev as usize).into()) };
+}
+
+/// Threshold used by the `critical-section-mintthresh` implementation
+static CS_THRESHOLD: AtomicU8 = AtomicU8::new(0xff);
+
+/// Sets the interrupt level held off by `critical_section::with`
+///
+/// Defaults to `0xff`, i.e., all interrupts are held off like with a global
+/// disable. Lowering it allows interrupts above `level` to run during critical
+/// sections.
+///
+/// Only has an effect with the `critical-section-mintthresh` feature.
+///
+/// # Safety
+///
+/// * No interrupt handler above `level` may use `critical_section`, or it could
+///   enter a critical section while another one is held, aliasing the data the
+///   critical section protects.
+/// * Must not be called from within a critical section.
+#[inline]
+pub unsafe fn set_critical_section_threshold(level: u8) {
+    CS_THRESHOLD.store(level, Ordering::Relaxed);
+}
+
+/// Returns the interrupt level held off by `critical_section::with`
+#[inline]
+pub fn critical_section_threshold() -> u8 {
+    CS_THRESHOLD.load(Ordering::Relaxed)
+}
+
+#[cfg(feature = "critical-section-mintthresh")]
+mod mintthresh_cs {
+    use critical_section::{set_impl, Impl, RawRestoreState};
+
+    struct MintthreshCriticalSection;
+    set_impl!(MintthreshCriticalSection);
+
+    unsafe impl Impl for MintthreshCriticalSection {
+        #[inline]
+        unsafe fn acquire() -> RawRestoreState {
+            super::raise_threshold(super::critical_section_threshold())
+        }
+
+        #[inline]
+        unsafe fn release(prev: RawRestoreState) {
+            super::restore_threshold(prev)
+        }
+    }
+}
@@ -0,0 +1,55 @@
+// Interrupt table, the single source of truth for Atalanta interrupt numbers
+//
+// This file is included by `src/interrupt.rs`, which generates the `Interrupt`
+// enum, and by `build.rs`, which generates the vector table and the default
+// trap entry points in `memory.x`. Adding an interrupt only requires a new line
+// here.
+interrupt_table! {
+    // SupervisorSoft = 1, sa. `STANDARD_VECTORS` in build.rs
+    MachineSoft = 3,
+    // SupervisorTimer = 5,
+    MachineTimer = 7,
+    // SupervisorExternal = 9,
+    MachineExternal = 11,
+    /// UART interrupt (Non-standard, overrides S-mode software interrupt
+    /// mapping.)
+    Uart = 17,
+    Gpio = 18,
+    SpiRxTxIrq = 19,
+    /// SPI end of transmission
+    SpiEotIrq = 20,
+    /// Timer 0 overflow
+    Timer0Ovf = 21,
+    /// Timer 0 compare
+    Timer0Cmp = 22,
+    /// Timer1 overflow
+    Timer1Ovf = 23,
+    /// Timer1 compare
+    Timer1Cmp = 24,
+    /// Timer2 overflow
+    Timer2Ovf = 25,
+    /// Timer2 compare
+    Timer2Cmp = 26,
+    /// Timer3 overflow
+    Timer3Ovf = 27,
+    /// Timer3 compare
+    Timer3Cmp = 28,
+    /// Non-maskable interrupt, carried over from standard Ibex
+    Nmi = 31,
+    Dma0 = 32,
+    Dma1 = 33,
+    Dma2 = 34,
+    Dma3 = 35,
+    Dma4 = 36,
+    Dma5 = 37,
+    Dma6 = 38,
+    Dma7 = 39,
+    Dma8 = 40,
+    Dma9 = 41,
+    Dma10 = 42,
+    Dma11 = 43,
+    Dma12 = 44,
+    Dma13 = 45,
+    Dma14 = 46,
+    Dma15 = 47,
+}
@@ -2,6 +2,9 @@
 //!
 //! Each API call here also inits the leds as appropriate, incurring some
 //! overhead.
+//!
+//! The leds are driven without owning their pins, so all calls are `unsafe`.
+//! Use the pins split from [Gpio](crate::gpio::Gpio) for owned access.
 use crate::gpio::GpioLo;
 use bitmask_enum::bitmask;
 
@@ -14,8 +17,11 @@ pub enum Led {
     Ld3 = 0b1 << 24,
 }
 
+/// # Safety
+///
+/// * The pin of `led` must not be owned by a [Pin](crate::gpio::Pin).
 #[inline]
-pub fn led_on(led: Led) {
+pub unsafe fn led_on(led: Led) {
     // Init
     let bits = led.bits;
     GpioLo::en(bits);
@@ -25,8 +31,11 @@ pub fn led_on(led: Led) {
     GpioLo::set_high(bits);
 }
 
+/// # Safety
+///
+/// * The pin of `led` must not be owned by a [Pin](crate::gpio::Pin).
 #[inline]
-pub fn led_off(led: Led) {
+pub unsafe fn led_off(led: Led) {
     // Init
     let bits = led.bits;
     GpioLo::en(bits);
@@ -36,8 +45,11 @@ pub fn led_off(led: Led) {
     GpioLo::set_low(led.bits);
 }
 
+/// # Safety
+///
+/// * The pin of `led` must not be owned by a [Pin](crate::gpio::Pin).
 #[inline]
-pub fn led_toggle(led: Led) {
+pub unsafe fn led_toggle(led: Led) {
     // Init
     let bits = led.bits;
     GpioLo::en(bits);
@@ -47,8 +59,11 @@ pub fn led_toggle(led: Led) {
     GpioLo::toggle(led.bits);
 }
 
+/// # Safety
+///
+/// * The pin of `led` must not be owned by a [Pin](crate::gpio::Pin).
 #[inline]
-pub fn led_set(led: Led, set: bool) {
+pub unsafe fn led_set(led: Led, set: bool) {
     if set {
         led_on(led);
     } else {
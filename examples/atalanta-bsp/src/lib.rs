@@ -4,12 +4,19 @@
 pub mod clic;
 #[cfg(not(feature = "ufmt"))]
 mod core_sprint;
+pub mod cycles;
+pub mod dma;
 pub mod gpio;
-mod interrupt;
+pub mod interrupt;
 pub mod led;
 pub mod mmap;
 pub mod mtimer;
+pub mod peripherals;
 pub mod register;
+pub mod rtic;
+pub mod sink;
+pub mod spi;
+pub mod srp;
 pub mod tb;
 pub mod timer_group;
 #[cfg(feature = "rt")]
@@ -20,14 +27,24 @@ mod ufmt_debug;
 #[cfg(feature = "ufmt")]
 mod ufmt_sprint;
 
+#[cfg(all(
+    feature = "critical-section-single-hart",
+    feature = "critical-section-mintthresh"
+))]
+compile_error!("Select at most one critical-section implementation");
+
 #[cfg(not(any(feature = "fpga", feature = "rtl-tb")))]
 compile_error!(
     "Select one of -Ffpga -Frtl-tb, BSP supports FPGA and RTL testbench implementations only"
 );
 
+pub use embedded_hal;
 pub use embedded_io;
 pub use fugit;
 pub use interrupt::{nested, Interrupt};
+#[cfg(feature = "log")]
+pub use log;
+pub use peripherals::Peripherals;
 pub use riscv;
 #[cfg(feature = "rt")]
 pub use riscv_rt::{self as rt, interrupt};
@@ -38,6 +55,10 @@ pub use ufmt;
 #[cfg(feature = "nest-continue")]
 atalanta_bsp_macros::generate_continue_nested_trap!();
 
+// Generate the software vectored `_start_mnxti_trap` symbol
+#[cfg(feature = "mnxti")]
+atalanta_bsp_macros::generate_mnxti_trap!();
+
 // Re-export macros for nested interrupts
 pub use atalanta_bsp_macros::{
     generate_continue_nested_trap, generate_nested_trap_entry, generate_pcs_trap_entry,
@@ -46,15 +67,6 @@ pub use atalanta_bsp_macros::{
 
 use core::arch::asm;
 
-/// Placeholder for RTIC
-pub struct Peripherals {}
-
-impl Peripherals {
-    pub unsafe fn steal() -> Self {
-        Self {}
-    }
-}
-
 pub const CPU_FREQ: u32 = match () {
     #[cfg(feature = "rtl-tb")]
     () => 100_000_000,
@@ -72,6 +84,12 @@ const fn nop_mult() -> u32 {
 }
 pub const NOPS_PER_SEC: u32 = CPU_FREQ / nop_mult();
 
+/// Spins for `t` nops
+///
+/// The duration depends on the optimization level, prefer the
+/// [DelayNs](embedded_hal::delay::DelayNs) implementations on
+/// [MTimer](mtimer::MTimer) and [Timer](timer_group::Timer) for exact delays,
+/// or [delay_cycles](cycles::delay_cycles) for short ones.
 pub fn asm_delay(t: u32) {
     for _ in 0..t {
         unsafe { asm!("nop") }
@@ -188,8 +206,9 @@ pub fn unmask_u8(addr: usize, unmask: u8) {
 #[allow(unused_variables)]
 fn panic_handler(info: &core::panic::PanicInfo) -> ! {
     // Initialize UART if not initialized
-    if !unsafe { crate::uart::UART_IS_INIT } {
-        crate::uart::ApbUart::init(crate::CPU_FREQ, crate::tb::DEFAULT_BAUD);
+    if !crate::uart::UART_IS_INIT.load(core::sync::atomic::Ordering::Relaxed) {
+        // SAFETY: no driver owns the UART, since it has not been initialized
+        unsafe { crate::uart::ApbUart::init(crate::CPU_FREQ, crate::tb::DEFAULT_BAUD) };
     }
 
     #[cfg(not(feature = "ufmt"))]
@@ -1,4 +1,4 @@
-pub const APB_TIMER_BASE: usize = 0x3_0300;
+pub const APB_TIMER_BASE: usize = super::rt_pkg::APB_TIMER_START_ADDR;
 
 pub const TIMER0_ADDR: usize = APB_TIMER_BASE;
 pub const TIMER1_ADDR: usize = APB_TIMER_BASE + 0x10;
@@ -37,3 +37,7 @@ pub const TIMER0: *mut RegisterBlock = TIMER0_ADDR as *mut _;
 pub const TIMER1: *mut RegisterBlock = TIMER1_ADDR as *mut _;
 pub const TIMER2: *mut RegisterBlock = TIMER2_ADDR as *mut _;
 pub const TIMER3: *mut RegisterBlock = TIMER3_ADDR as *mut _;
+
+const _: () = assert!(
+    TIMER3_ADDR + core::mem::size_of::<RegisterBlock>() - 1 <= super::rt_pkg::APB_TIMER_END_ADDR
+);
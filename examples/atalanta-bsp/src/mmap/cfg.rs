@@ -1,4 +1,4 @@
-pub const CFG_BASE: usize = 0x3_0500;
+pub const CFG_BASE: usize = super::rt_pkg::CFG_REGS_START_ADDR;
 
 /// # Safety
 ///
@@ -1,4 +1,4 @@
-pub const CLIC_BASE_ADDR: usize = 0x5_0000;
+pub const CLIC_BASE_ADDR: usize = super::rt_pkg::CLIC_START_ADDR;
 
 /* Register width */
 pub const CLIC_PARAM_REG_WIDTH: usize = 8;
@@ -0,0 +1,39 @@
+//! Register maps for [NanoDMA](https://github.com/ANurmi/NanoDMA)
+//!
+//! The configuration interface matches the usage in
+//! `examples/smoke_tests/src/dma_test/dma_test.c`.
+
+pub const DMA_BASE: usize = super::rt_pkg::DMA_RULE_START;
+
+/// Transfer control
+///
+/// * `[15:0]` - transfer length in 32-bit words
+/// * `[31]` - start transfer
+pub const DMA_CTRL_OFS: usize = 0x0;
+pub const DMA_CTRL_LEN_MASK: u32 = 0xffff;
+pub const DMA_CTRL_START_BIT: u32 = 0b1 << 31;
+
+/// Source address
+///
+/// Must be 4-byte aligned.
+pub const DMA_SRC_OFS: usize = 0x4;
+
+/// Destination address
+///
+/// Must be 4-byte aligned.
+pub const DMA_DST_OFS: usize = 0x8;
+
+#[repr(C)]
+pub struct RegisterBlock {
+    /// 0x0..0x4 Control
+    pub ctrl: u32,
+    /// 0x4..0x8 Source address
+    pub src: u32,
+    /// 0x8..0xc Destination address
+    pub dst: u32,
+}
+
+pub const DMA: *mut RegisterBlock = DMA_BASE as *mut _;
+
+const _: () =
+    assert!(DMA_BASE + core::mem::size_of::<RegisterBlock>() <= super::rt_pkg::DMA_RULE_END);
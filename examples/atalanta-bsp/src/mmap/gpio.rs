@@ -2,7 +2,7 @@
 //!
 //! Based on [Excel](https://github.com/pulp-platform/apb_gpio/blob/master/docs/APB_reference.xlsx)
 
-pub const GPIO_BASE: usize = 0x3_0000;
+pub const GPIO_BASE: usize = super::rt_pkg::GPIO_START_ADDR;
 
 #[repr(C)]
 pub struct RegisterBlock {
@@ -96,3 +96,6 @@ pub struct PadRegisterBlock {
     /// - `bit[8*i+5 : 8*i+4] = 0b1`: high drive strength
     pub pad_cfg: [u32; 4],
 }
+
+const _: () =
+    assert!(GPIO_BASE + core::mem::size_of::<RegisterBlock>() - 1 <= super::rt_pkg::GPIO_END_ADDR);
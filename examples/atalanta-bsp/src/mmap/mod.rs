@@ -1,6 +1,7 @@
 //! Memory maps for rt-ss
 //!
-//! Based on <src/ip/rt_pkg.sv>.
+//! Base addresses are generated from <src/ip/rt_pkg.sv> by `build.rs`, sa.
+//! [rt_pkg].
 
 // Some addresses are given in functional style but use all-caps for consistency
 #![allow(non_snake_case)]
@@ -10,6 +11,7 @@
 pub mod apb_timer;
 mod cfg;
 mod clic;
+pub mod dma;
 pub mod gpio;
 mod mtimer;
 mod spi;
@@ -21,4 +23,9 @@ pub use mtimer::*;
 pub use spi::*;
 pub use uart::*;
 
-pub const DEBUG_ADDR: usize = 0x0;
+/// Address map generated from <src/ip/rt_pkg.sv>
+pub mod rt_pkg {
+    include!(concat!(env!("OUT_DIR"), "/rt_pkg.rs"));
+}
+
+pub const DEBUG_ADDR: usize = rt_pkg::DBG_RULE_START;
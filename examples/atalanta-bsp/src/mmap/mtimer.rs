@@ -6,10 +6,13 @@
 //! - [apb_mtimer (SV)](.../src/ip/apb_mtimer.sv)
 //!
 //! See also [crate::mmap::timer_group] for the general purpose timer group.
-pub const MTIMER_BASE: usize = 0x3_0200;
+pub const MTIMER_BASE: usize = super::rt_pkg::MTIMER_START_ADDR;
 
 pub const MTIME_LOW_ADDR_OFS: usize = 0;
 pub const MTIME_HIGH_ADDR_OFS: usize = 4;
 pub const MTIMECMP_LOW_ADDR_OFS: usize = 8;
 pub const MTIMECMP_HIGH_ADDR_OFS: usize = 12;
 pub const MTIME_CTRL_ADDR_OFS: usize = 16;
+
+// The RTL maps the address of the last register only, not all of its bytes
+const _: () = assert!(MTIMER_BASE + MTIME_CTRL_ADDR_OFS <= super::rt_pkg::MTIMER_END_ADDR);
@@ -1 +1,104 @@
-pub const SPI_BASE_ADDR: usize = 0x3_0400;
+//! Register maps for [PULP APB SPI master](https://github.com/pulp-platform/apb_spi_master)
+//!
+//! Atalanta instantiates the IP with a `BUFFER_DEPTH` of 10 words for both TX
+//! and RX FIFOs.
+pub const SPI_BASE_ADDR: usize = super::rt_pkg::SPI_START_ADDR;
+
+/// Status register
+///
+/// ## Write
+///
+/// - `[0]`: start a standard read transaction
+/// - `[1]`: start a standard write transaction
+/// - `[2]`: start a quad read transaction
+/// - `[3]`: start a quad write transaction
+/// - `[4]`: software reset, clears the FIFOs
+/// - `[11:8]`: one-hot chip select for the transaction
+///
+/// ## Read
+///
+/// - `[6:0]`: controller state, `0b1` when idle
+/// - `[23:16]`: number of words in the RX FIFO
+/// - `[31:24]`: number of words in the TX FIFO
+pub const SPI_STATUS_OFS: usize = 0x00;
+pub const SPI_STATUS_RD_BIT: u32 = 0b1;
+pub const SPI_STATUS_WR_BIT: u32 = 0b1 << 1;
+pub const SPI_STATUS_QRD_BIT: u32 = 0b1 << 2;
+pub const SPI_STATUS_QWR_BIT: u32 = 0b1 << 3;
+pub const SPI_STATUS_SWRST_BIT: u32 = 0b1 << 4;
+pub const SPI_STATUS_CS_BIT_IDX: u32 = 8;
+pub const SPI_STATUS_STATE_MASK: u32 = 0x7f;
+pub const SPI_STATUS_STATE_IDLE: u32 = 0b1;
+pub const SPI_STATUS_RX_ELEMS_BIT_IDX: u32 = 16;
+pub const SPI_STATUS_TX_ELEMS_BIT_IDX: u32 = 24;
+pub const SPI_STATUS_ELEMS_MASK: u32 = 0xff;
+
+/// Clock divider
+///
+/// - `[7:0]`: SPI clock is the peripheral clock divided by `2 * (div + 1)`
+pub const SPI_CLKDIV_OFS: usize = 0x04;
+pub const SPI_CLKDIV_MASK: u32 = 0xff;
+
+/// Command, transmitted MSB first before the address
+pub const SPI_CMD_OFS: usize = 0x08;
+
+/// Address, transmitted MSB first after the command
+pub const SPI_ADDR_OFS: usize = 0x0C;
+
+/// Transaction lengths in bits
+///
+/// - `[5:0]`: command length
+/// - `[13:8]`: address length
+/// - `[31:16]`: data length
+pub const SPI_LEN_OFS: usize = 0x10;
+pub const SPI_LEN_CMD_BIT_IDX: u32 = 0;
+pub const SPI_LEN_CMD_MASK: u32 = 0x3f;
+pub const SPI_LEN_ADDR_BIT_IDX: u32 = 8;
+pub const SPI_LEN_ADDR_MASK: u32 = 0x3f;
+pub const SPI_LEN_DATA_BIT_IDX: u32 = 16;
+pub const SPI_LEN_DATA_MASK: u32 = 0xffff;
+
+/// Dummy cycles
+///
+/// - `[15:0]`: dummy cycles between address and read data
+/// - `[31:16]`: dummy cycles between address and write data
+pub const SPI_DUMMY_OFS: usize = 0x14;
+pub const SPI_DUMMY_RD_BIT_IDX: u32 = 0;
+pub const SPI_DUMMY_WR_BIT_IDX: u32 = 16;
+
+/// TX FIFO, write-only
+///
+/// Data is transmitted MSB first.
+pub const SPI_TXFIFO_OFS: usize = 0x18;
+
+/// RX FIFO, read-only
+///
+/// Data is received into the LSBs, i.e., a word that is only partially filled
+/// at the end of a transaction holds the received bits in the LSBs.
+pub const SPI_RXFIFO_OFS: usize = 0x20;
+
+/// Interrupt configuration
+///
+/// Configures the RX/TX FIFO threshold interrupt ([crate::Interrupt::SpiRxTxIrq]).
+/// The end of transmission interrupt ([crate::Interrupt::SpiEotIrq]) is always
+/// raised.
+///
+/// - `[4:0]`: TX FIFO threshold
+/// - `[12:8]`: RX FIFO threshold
+/// - `[20:16]`: TX word count
+/// - `[28:24]`: RX word count
+/// - `[31]`: enable threshold interrupt
+pub const SPI_INTCFG_OFS: usize = 0x24;
+pub const SPI_INTCFG_TH_TX_BIT_IDX: u32 = 0;
+pub const SPI_INTCFG_TH_RX_BIT_IDX: u32 = 8;
+pub const SPI_INTCFG_CNT_TX_BIT_IDX: u32 = 16;
+pub const SPI_INTCFG_CNT_RX_BIT_IDX: u32 = 24;
+pub const SPI_INTCFG_EN_BIT: u32 = 0b1 << 31;
+
+/// Interrupt status
+pub const SPI_INTSTA_OFS: usize = 0x28;
+
+/// Depth of RX and TX FIFOs in 32-bit words
+pub const SPI_FIFO_DEPTH: u32 = 10;
+
+const _: () = assert!(SPI_BASE_ADDR + SPI_INTSTA_OFS + 3 <= super::rt_pkg::SPI_END_ADDR);
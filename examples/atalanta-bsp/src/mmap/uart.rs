@@ -2,7 +2,7 @@
 //!
 //! Some implementations of APB UART space registers with one byte offset but
 //! Atalanta uses 4 bytes as tends to be conventional.
-pub const UART_BASE: usize = 0x3_0100;
+pub const UART_BASE: usize = super::rt_pkg::UART_START_ADDR;
 
 /// Receiver Buffer Register (RBR) / Transmitter Holding Register (THR) /
 /// Divisor Latch LSB (DLL)
@@ -68,6 +68,16 @@ pub const UART_FCR_FIFO_TX_RESET_BIT: u8 = 0b1 << 2;
 pub const UART_FCR_TRIG_RX_LSB: u8 = 0b1 << 6;
 pub const UART_FCR_TRIG_RX_MSB: u8 = 0b1 << 7;
 
+/// RX FIFO trigger level
+#[derive(Clone, Copy)]
+#[repr(u8)]
+pub enum UartFcrTrigLevel {
+    Bytes1 = 0b00 << 6,
+    Bytes4 = 0b01 << 6,
+    Bytes8 = 0b10 << 6,
+    Bytes14 = 0b11 << 6,
+}
+
 /// Line Control Register
 ///
 /// LCR configures the main operation of the uart. It configures the width of
@@ -83,12 +93,18 @@ pub const UART_FCR_TRIG_RX_MSB: u8 = 0b1 << 7;
 ///     - `0b1`: 1.5 stop bits for 5 bits data word OR 2 stop bits 6, 7 or 8
 ///       bits data word
 /// - `[3]`: parity enable bit
+/// - `[4]`: even parity select
+///     - `0b0`: odd parity
+///     - `0b1`: even parity
+/// - `[5]`: stick parity, parity bit is driven to the inverse of `LCR[4]`
+/// - `[6]`: break control, forces the serial output low
 /// - `[7]`: divisor latch access bit (DLAB)
 ///     - `0b0`: RBR, THR and IER accessible
 ///     - `0b1`: DLL and DLM accessible
 pub const UART_LCR_OFS: usize = 12;
 
 /// Data configuration bits
+#[derive(Clone, Copy)]
 #[repr(u8)]
 pub enum UartLcrDataBits {
     Bits5 = 0b00,
@@ -97,19 +113,31 @@ pub enum UartLcrDataBits {
     Bits8 = 0b11,
 }
 
+pub const UART_LCR_STOP_BIT: u8 = 0b1 << 2;
+pub const UART_LCR_PARITY_EN_BIT: u8 = 0b1 << 3;
+pub const UART_LCR_PARITY_EVEN_BIT: u8 = 0b1 << 4;
+pub const UART_LCR_PARITY_STICK_BIT: u8 = 0b1 << 5;
+pub const UART_LCR_BREAK_BIT: u8 = 0b1 << 6;
 /// Divisor Latch Access Bit
 pub const UART_LCR_DLAB_BIT: u8 = 0b1 << 7;
 
-/// Modem Control
+/// Modem Control Register
+///
+/// - `[1]`: request to send (RTS), required for automatic RTS
+/// - `[5]`: autoflow control enable
 pub const UART_MCR_OFS: usize = 16;
+pub const UART_MCR_RTS_BIT: u8 = 0b1 << 1;
+pub const UART_MCR_AFE_BIT: u8 = 0b1 << 5;
 
 /// Line Status Register
 ///
+/// Bits `[4:1]` are cleared on read.
+///
 /// - `[0]`: RX FIFO data valid
-/// - `[1]`: *not used*
-/// - `[2]`: parity error from the RX FIFO
-/// - `[3]`: *not used*
-/// - `[4]`: *not used*
+/// - `[1]`: overrun error, a character was lost due to a full RX FIFO
+/// - `[2]`: parity error in the character at the top of the RX FIFO
+/// - `[3]`: framing error in the character at the top of the RX FIFO
+/// - `[4]`: break interrupt, the input was held low for longer than a character
 /// - `[5]`: the TX FIFO is empty
 /// - `[6]`: shift register and TX FIFO are empty
 pub const UART_LSR_OFS: usize = 20;
@@ -121,4 +149,15 @@ pub const UART_SCRATCH_OFFSET_OFs: usize = 28;
 */
 
 pub const UART_LSR_RX_FIFO_VALID_BIT: u8 = 0b1;
+pub const UART_LSR_OVERRUN_BIT: u8 = 1 << 1;
+pub const UART_LSR_PARITY_BIT: u8 = 1 << 2;
+pub const UART_LSR_FRAMING_BIT: u8 = 1 << 3;
+pub const UART_LSR_BREAK_BIT: u8 = 1 << 4;
 pub const UART_LSR_TX_FIFO_EMPTY_BIT: u8 = 1 << 5;
+pub const UART_LSR_ERROR_MASK: u8 =
+    UART_LSR_OVERRUN_BIT | UART_LSR_PARITY_BIT | UART_LSR_FRAMING_BIT | UART_LSR_BREAK_BIT;
+
+/// Depth of RX and TX FIFOs in bytes
+pub const UART_FIFO_DEPTH: usize = 16;
+
+const _: () = assert!(UART_BASE + UART_LSR_OFS + 3 <= super::rt_pkg::UART_END_ADDR);
@@ -1,21 +1,52 @@
+pub mod queue;
+
+use core::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+
+use embedded_hal::delay::DelayNs;
+use riscv::asm::wfi;
+
 use crate::{
+    clic::Clic,
     mask_u32,
     mmap::{
         CFG_BASE, MTIMECMP_HIGH_ADDR_OFS, MTIMECMP_LOW_ADDR_OFS, MTIMER_BASE, MTIME_CTRL_ADDR_OFS,
         MTIME_HIGH_ADDR_OFS, MTIME_LOW_ADDR_OFS, PERIPH_CLK_DIV_OFS,
     },
-    read_u32, read_u8_masked, unmask_u32, write_u32, CPU_FREQ,
+    peripherals::MTIMER,
+    read_u32, read_u8_masked, unmask_u32, write_u32, Interrupt, CPU_FREQ,
 };
 
+/// Reads a 64-bit register as hi-lo-hi, retrying until the high word is
+/// stable
+#[inline]
+fn read_u64(hi_addr: usize, lo_addr: usize) -> u64 {
+    loop {
+        let hi = read_u32(hi_addr);
+        let lo = read_u32(lo_addr);
+        if read_u32(hi_addr) == hi {
+            return ((hi as u64) << 32) | lo as u64;
+        }
+    }
+}
+
 /// Machine Timer
 ///
 /// This timer is associated with [crate::Interrupt::MachineTimer]
 pub struct MTimer {}
 
 impl MTimer {
-    /// Returns the global mtimer instance
+    /// Returns the mtimer owned by `mtimer`
+    #[inline]
+    pub fn new(_mtimer: MTIMER) -> Self {
+        Self {}
+    }
+
+    /// # Safety
+    ///
+    /// Returns the global mtimer instance regardless of whether it's already
+    /// owned elsewhere, allowing aliased access.
     #[inline]
-    pub fn instance() -> Self {
+    pub unsafe fn instance() -> Self {
         Self {}
     }
 
@@ -36,6 +67,20 @@ impl MTimer {
         mask_u32(MTIMER_BASE + MTIME_CTRL_ADDR_OFS, 0b1);
     }
 
+    /// Returns the prescaler. mtime advances once every `prescaler + 1`
+    /// peripheral clock cycles.
+    #[inline]
+    pub fn prescaler(&self) -> u32 {
+        (read_u32(MTIMER_BASE + MTIME_CTRL_ADDR_OFS) >> 8) & 0b111
+    }
+
+    /// Returns the effective tick rate in Hz, i.e., [CPU_FREQ] divided by the
+    /// peripheral clock divider and the prescaler
+    #[inline]
+    pub fn tick_hz(&self) -> u32 {
+        CPU_FREQ / cycles_per_tick() as u32
+    }
+
     /// Stops the count & disables the interrupt line on the core
     ///
     /// Note that disabling the mtimer can be unexpected behavior.
@@ -44,14 +89,16 @@ impl MTimer {
         unmask_u32(MTIMER_BASE + MTIME_CTRL_ADDR_OFS, 0b1);
     }
 
-    /// N.b., you may sometimes get a disjoint value if the function gets
-    /// interrupted in the middle of the read transaction
+    /// Reads mtime
+    ///
+    /// The read is retried if the low word wraps in between reading the high &
+    /// low words, so the value is never torn.
     #[inline]
     pub fn counter(&self) -> u64 {
-        let hi = read_u32(MTIMER_BASE + MTIME_HIGH_ADDR_OFS);
-        let lo = read_u32(MTIMER_BASE + MTIME_LOW_ADDR_OFS);
-        let mtime = ((hi as u64) << 32) | lo as u64;
-        mtime
+        read_u64(
+            MTIMER_BASE + MTIME_HIGH_ADDR_OFS,
+            MTIMER_BASE + MTIME_LOW_ADDR_OFS,
+        )
     }
 
     #[inline]
@@ -74,13 +121,14 @@ impl MTimer {
 
     /// Gets the timer compare value
     ///
-    /// Safety: needs to be called in an interrupt critical-section, otherwise
-    /// you risk getting interrupted in between reading the hi & low address and
-    /// getting a disjoint value
+    /// The read is retried if the compare value changes in between reading the
+    /// high & low words, so the value is never torn.
     #[inline]
-    pub unsafe fn cmp(&mut self) -> u64 {
-        ((read_u32(MTIMER_BASE + MTIMECMP_HIGH_ADDR_OFS) as u64) << 32)
-            | read_u32(MTIMER_BASE + MTIMECMP_LOW_ADDR_OFS) as u64
+    pub fn cmp(&mut self) -> u64 {
+        read_u64(
+            MTIMER_BASE + MTIMECMP_HIGH_ADDR_OFS,
+            MTIMER_BASE + MTIMECMP_LOW_ADDR_OFS,
+        )
     }
 
     /// Sets the timer compare value
@@ -105,6 +153,21 @@ impl MTimer {
     pub fn into_oneshot(self) -> OneShot {
         OneShot(self)
     }
+
+    #[inline]
+    pub fn into_sleeping_delay(self) -> SleepingDelay {
+        SleepingDelay(self)
+    }
+
+    /// Starts the count if needed and returns the first mtime at which at
+    /// least `ns` have passed
+    #[inline]
+    fn delay_deadline(&mut self, ns: u32) -> u64 {
+        self.enable();
+        let ticks = (ns as u64 * CPU_FREQ as u64).div_ceil(1_000_000_000 * cycles_per_tick());
+        // The current tick may be about to end, so wait for one more
+        self.counter() + ticks + 1
+    }
 }
 
 /// Machine Timer, lower bits only
@@ -179,6 +242,7 @@ impl MTimerLo {
 
 const DENOM: u32 = CPU_FREQ;
 pub type Duration = fugit::Duration<u64, 1, DENOM>;
+pub type Instant = fugit::Instant<u64, 1, DENOM>;
 
 pub struct OneShot(MTimer);
 
@@ -186,11 +250,9 @@ impl OneShot {
     /// Schedules the `MachineTimer` interrupt to trigger after `duration`
     #[inline]
     pub fn start(&mut self, duration: Duration) {
-        // Read current peripheral clock divider to dynamically fixup fugit::Duration
-        let pclk_div = unsafe { read_u8_masked(CFG_BASE + PERIPH_CLK_DIV_OFS, 0xf) };
-
+        // Fixup fugit::Duration for the peripheral clock divider & prescaler
         let cnt = self.0.counter();
-        self.0.set_cmp(cnt + duration.ticks() / pclk_div as u64);
+        self.0.set_cmp(cnt + duration.ticks() / cycles_per_tick());
         self.0.enable();
     }
 
@@ -206,13 +268,14 @@ impl OneShot {
 
     /// Gets the timer compare value
     ///
-    /// Safety: needs to be called in an interrupt critical-section, otherwise
-    /// you risk getting interrupted in between reading the hi & low address and
-    /// getting a disjoint value
+    /// The read is retried if the compare value changes in between reading the
+    /// high & low words, so the value is never torn.
     #[inline]
-    pub unsafe fn cmp(&mut self) -> u64 {
-        ((read_u32(MTIMER_BASE + MTIMECMP_HIGH_ADDR_OFS) as u64) << 32)
-            | read_u32(MTIMER_BASE + MTIMECMP_LOW_ADDR_OFS) as u64
+    pub fn cmp(&mut self) -> u64 {
+        read_u64(
+            MTIMER_BASE + MTIMECMP_HIGH_ADDR_OFS,
+            MTIMER_BASE + MTIMECMP_LOW_ADDR_OFS,
+        )
     }
 }
 
@@ -221,3 +284,166 @@ impl From<OneShot> for MTimer {
         value.0
     }
 }
+
+/// Busy-waits on mtime
+///
+/// Starts the count if it's stopped, but leaves mtimecmp untouched.
+impl DelayNs for MTimer {
+    #[inline]
+    fn delay_ns(&mut self, ns: u32) {
+        let deadline = self.delay_deadline(ns);
+        while self.counter() < deadline {}
+    }
+}
+
+/// Delay that sleeps on `wfi` until mtime reaches mtimecmp
+///
+/// Interrupts are masked while sleeping so that the `MachineTimer` interrupt
+/// wakes the core without trapping. Other interrupts are serviced after the
+/// delay. mtimecmp is left at `u64::MAX`.
+///
+/// The delay owns the mtimer, so it cannot coexist with [Monotonic] and the
+/// timer [queue], which program mtimecmp themselves.
+pub struct SleepingDelay(MTimer);
+
+impl DelayNs for SleepingDelay {
+    #[inline]
+    fn delay_ns(&mut self, ns: u32) {
+        let mtimer = &mut self.0;
+        let deadline = mtimer.delay_deadline(ns);
+        let ie = Clic::ie(Interrupt::MachineTimer);
+
+        riscv::interrupt::free(|| {
+            let was_enabled = ie.is_enabled();
+            mtimer.set_cmp(deadline);
+            // SAFETY: interrupts are masked globally
+            unsafe { ie.enable() };
+
+            // Wake-ups by other interrupts are spurious
+            while mtimer.counter() < deadline {
+                wfi();
+            }
+
+            // Lower the interrupt line before restoring the enable
+            mtimer.set_cmp(u64::MAX);
+            // SAFETY: edge-triggered configurations may have latched the
+            // wake-up, software writes are supported
+            unsafe { Clic::ip(Interrupt::MachineTimer).unpend() };
+            if !was_enabled {
+                ie.disable();
+            }
+        });
+    }
+}
+
+impl From<SleepingDelay> for MTimer {
+    fn from(value: SleepingDelay) -> Self {
+        value.0
+    }
+}
+
+/// Monotonic clock on the mtimer, the BSP's time base
+///
+/// Time is measured in [Instant]s at [CPU_FREQ], fixed up for the peripheral
+/// clock divider & the mtimer prescaler, which makes it compatible with
+/// [Duration] and [OneShot::start]. Start the clock with [Monotonic::start],
+/// after which the associated functions can be called from any context, like an
+/// RTIC monotonic.
+///
+/// The clock owns the mtimer, and mtimecmp is reserved for the timer [queue].
+pub struct Monotonic;
+
+/// Set by [Monotonic::start], after which the mtimer is owned by the clock
+static MONOTONIC_STARTED: AtomicBool = AtomicBool::new(false);
+
+/// CPU clock cycles per mtimer tick, latched by [Monotonic::start]
+static MONOTONIC_CYCLES_PER_TICK: AtomicU32 = AtomicU32::new(1);
+
+impl Monotonic {
+    /// Starts the count, taking ownership of the mtimer
+    ///
+    /// The count is not reset, so that instants remain comparable. The
+    /// peripheral clock divider & the prescaler are latched here, so that
+    /// reprogramming the divider later on cannot make [Monotonic::now] jump.
+    #[inline]
+    pub fn start(mut mtimer: MTimer) {
+        // At most 15 * 8
+        MONOTONIC_CYCLES_PER_TICK.store(cycles_per_tick() as u32, Ordering::Relaxed);
+        mtimer.set_cmp(u64::MAX);
+        mtimer.enable();
+        MONOTONIC_STARTED.store(true, Ordering::Release);
+    }
+
+    /// Returns `true` if the clock owns the mtimer, sa. [Monotonic::start]
+    #[inline]
+    pub fn is_started() -> bool {
+        MONOTONIC_STARTED.load(Ordering::Acquire)
+    }
+
+    /// Returns the current time
+    #[inline]
+    pub fn now() -> Instant {
+        // SAFETY: the counter is only read
+        let mtime = unsafe { MTimer::instance() }.counter();
+        Instant::from_ticks(mtime * Self::cycles_per_tick())
+    }
+
+    /// Returns the time passed since `since`
+    #[inline]
+    pub fn elapsed(since: Instant) -> Duration {
+        Self::now()
+            .checked_duration_since(since)
+            .unwrap_or(Duration::from_ticks(0))
+    }
+
+    /// Busy-waits until `instant`
+    #[inline]
+    pub fn delay_until(instant: Instant) {
+        while Self::now() < instant {}
+    }
+
+    /// Busy-waits for `duration`
+    #[inline]
+    pub fn delay(duration: Duration) {
+        Self::delay_until(Self::now() + duration)
+    }
+
+    /// Schedules the `MachineTimer` interrupt to trigger at `instant`
+    ///
+    /// An instant in the past triggers the interrupt immediately. Only the
+    /// timer [queue] may program the compare, so that it never loses track of
+    /// its earliest deadline.
+    #[inline]
+    pub(crate) fn set_compare(instant: Instant) {
+        // SAFETY: mtimer is owned by the clock after `start`
+        let mut mtimer = unsafe { MTimer::instance() };
+        mtimer.set_cmp(instant.ticks().div_ceil(Self::cycles_per_tick()));
+    }
+
+    /// Unschedules the `MachineTimer` interrupt, which lowers the interrupt
+    /// line
+    #[inline]
+    pub(crate) fn clear_compare() {
+        // SAFETY: mtimer is owned by the clock after `start`
+        unsafe { MTimer::instance() }.set_cmp(u64::MAX);
+    }
+
+    /// Returns the CPU clock cycles per tick latched by [Monotonic::start]
+    #[inline]
+    fn cycles_per_tick() -> u64 {
+        MONOTONIC_CYCLES_PER_TICK.load(Ordering::Relaxed) as u64
+    }
+}
+
+/// Returns the number of CPU clock cycles per mtimer tick, i.e., the peripheral
+/// clock divider times `prescaler + 1`
+///
+/// A divider of zero is treated as one, so that the result is never zero.
+#[inline]
+fn cycles_per_tick() -> u64 {
+    // SAFETY: valid, aligned address
+    let pclk_div = unsafe { read_u8_masked(CFG_BASE + PERIPH_CLK_DIV_OFS, 0xf) }.max(1);
+    // SAFETY: the control register is only read
+    let prescaler = unsafe { MTimer::instance() }.prescaler();
+    pclk_div as u64 * (prescaler as u64 + 1)
+}
@@ -0,0 +1,305 @@
+//! Software timers multiplexed on the `MachineTimer` interrupt
+//!
+//! Any number of one-shot and periodic timers, up to [MAX_TIMERS], are kept in
+//! a static queue sorted by deadline. mtimecmp is always programmed to the
+//! earliest deadline, and the `MachineTimer` interrupt handler, which must call
+//! [on_interrupt], fires the expired timers and reprograms mtimecmp to the next
+//! one.
+//!
+//! Time is measured on [Monotonic], which must be started before scheduling,
+//! or scheduling panics. Starting the clock consumes the
+//! [MTimer](super::MTimer), and the queue is the only user of mtimecmp from
+//! then on, so the compare cannot be moved under the queue by other mtimer
+//! drivers.
+//!
+//! # Example
+//!
+//! ```ignore
+//! static TICK: AtomicBool = AtomicBool::new(false);
+//!
+//! Monotonic::start(MTimer::new(p.mtimer));
+//! unsafe { queue::enable_interrupt(0x88) };
+//! queue::schedule_periodic(Duration::millis(10), Action::Flag(&TICK)).unwrap();
+//! queue::schedule(Duration::millis(25), Action::Callback(on_timeout)).unwrap();
+//!
+//! #[interrupt]
+//! fn MachineTimer() {
+//!     queue::on_interrupt();
+//! }
+//! ```
+use core::{
+    ptr,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use super::{Duration, Instant, Monotonic};
+use crate::{
+    clic::{Clic, Polarity, Trig},
+    Interrupt,
+};
+
+/// Capacity of the timer queue
+pub const MAX_TIMERS: usize = 32;
+
+/// What to do when a timer expires
+#[derive(Clone, Copy)]
+pub enum Action {
+    /// Call the function from the `MachineTimer` interrupt handler
+    Callback(fn()),
+    /// Set the flag, e.g., to be polled from thread mode
+    Flag(&'static AtomicBool),
+}
+
+impl Action {
+    #[inline]
+    fn fire(self) {
+        match self {
+            Action::Callback(f) => f(),
+            Action::Flag(flag) => flag.store(true, Ordering::Release),
+        }
+    }
+}
+
+/// Identifies a scheduled timer, sa. [cancel]
+///
+/// Ids are drawn from a 64-bit counter and are not reused, so a stale id
+/// cannot cancel a later timer.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "ufmt", derive(crate::ufmt::derive::uDebug))]
+pub struct TimerId(u64);
+
+/// Error type for scheduling timers
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "ufmt", derive(crate::ufmt::derive::uDebug))]
+pub enum QueueError {
+    /// The queue holds [MAX_TIMERS] timers already
+    Full,
+    /// The period of a periodic timer is zero
+    ZeroPeriod,
+}
+
+#[derive(Clone, Copy)]
+struct Entry {
+    id: TimerId,
+    deadline: Instant,
+    /// Period of a periodic timer, `None` for a one-shot timer
+    period: Option<Duration>,
+    action: Action,
+}
+
+/// Timers sorted by deadline, earliest first
+struct Queue {
+    entries: [Option<Entry>; MAX_TIMERS],
+    len: usize,
+    next_id: u64,
+}
+
+impl Queue {
+    const fn new() -> Self {
+        const NONE: Option<Entry> = None;
+        Self {
+            entries: [NONE; MAX_TIMERS],
+            len: 0,
+            next_id: 0,
+        }
+    }
+
+    /// Inserts `entry` after all entries with an earlier or equal deadline
+    #[inline]
+    fn insert(&mut self, entry: Entry) -> Result<(), QueueError> {
+        if self.len == MAX_TIMERS {
+            return Err(QueueError::Full);
+        }
+        let mut idx = self.len;
+        while idx > 0 && self.entries[idx - 1].is_some_and(|e| e.deadline > entry.deadline) {
+            self.entries[idx] = self.entries[idx - 1];
+            idx -= 1;
+        }
+        self.entries[idx] = Some(entry);
+        self.len += 1;
+        Ok(())
+    }
+
+    #[inline]
+    fn remove(&mut self, idx: usize) -> Entry {
+        let entry = self.entries[idx].take().unwrap();
+        for i in idx..self.len - 1 {
+            self.entries[i] = self.entries[i + 1].take();
+        }
+        self.len -= 1;
+        entry
+    }
+
+    #[inline]
+    fn head(&self) -> Option<&Entry> {
+        self.entries[0].as_ref()
+    }
+
+    #[inline]
+    fn is_head_expired(&self) -> bool {
+        self.head()
+            .is_some_and(|head| head.deadline <= Monotonic::now())
+    }
+
+    /// Removes the earliest timer, rescheduling it if it's periodic
+    ///
+    /// Periods that have already passed are skipped, so a timer that is
+    /// serviced late cannot keep [on_interrupt] from returning.
+    #[inline]
+    fn pop(&mut self) -> Entry {
+        let entry = self.remove(0);
+        if let Some(period) = entry.period {
+            let mut deadline = entry.deadline + period;
+            let now = Monotonic::now();
+            if deadline <= now {
+                let missed = (now - deadline).ticks() / period.ticks() + 1;
+                deadline += Duration::from_ticks(missed * period.ticks());
+            }
+            // Cannot fail, the slot was just freed
+            let _ = self.insert(Entry { deadline, ..entry });
+        }
+        entry
+    }
+
+    #[inline]
+    fn next_id(&mut self) -> TimerId {
+        let id = TimerId(self.next_id);
+        self.next_id += 1;
+        id
+    }
+
+    /// Programs mtimecmp to the earliest deadline, or disables the compare if
+    /// the queue is empty
+    #[inline]
+    fn rearm(&self) {
+        match self.head() {
+            Some(head) => Monotonic::set_compare(head.deadline),
+            None => Monotonic::clear_compare(),
+        }
+    }
+}
+
+static mut QUEUE: Queue = Queue::new();
+
+/// Enables [Interrupt::MachineTimer] on the CLIC with interrupt level `level`
+///
+/// # Safety
+///
+/// * Enabling an interrupt source can break mask-based critical sections.
+#[inline]
+pub unsafe fn enable_interrupt(level: u8) {
+    Clic::configure(Interrupt::MachineTimer)
+        .trigger(Trig::Level)
+        .polarity(Polarity::Pos)
+        .shv(true)
+        .level(level)
+        .enable()
+        .keep();
+}
+
+/// Schedules a one-shot timer at `deadline`
+///
+/// A deadline in the past expires on the next interrupt.
+#[inline]
+pub fn schedule_at(deadline: Instant, action: Action) -> Result<TimerId, QueueError> {
+    insert(deadline, None, action)
+}
+
+/// Schedules a one-shot timer to expire after `duration`
+#[inline]
+pub fn schedule(duration: Duration, action: Action) -> Result<TimerId, QueueError> {
+    insert(Monotonic::now() + duration, None, action)
+}
+
+/// Schedules a periodic timer to first expire after `period`
+///
+/// Subsequent deadlines are a whole number of periods after the first one, so
+/// the timer does not drift with interrupt latency. Returns
+/// [QueueError::ZeroPeriod] if `period` is zero.
+#[inline]
+pub fn schedule_periodic(period: Duration, action: Action) -> Result<TimerId, QueueError> {
+    if period.ticks() == 0 {
+        return Err(QueueError::ZeroPeriod);
+    }
+    insert(Monotonic::now() + period, Some(period), action)
+}
+
+#[inline]
+fn insert(
+    deadline: Instant,
+    period: Option<Duration>,
+    action: Action,
+) -> Result<TimerId, QueueError> {
+    // Programming mtimecmp is only sound once the clock owns the mtimer
+    assert!(
+        Monotonic::is_started(),
+        "Monotonic must be started before scheduling"
+    );
+    riscv::interrupt::free(|| {
+        // SAFETY: exclusive access in critical section
+        let queue = unsafe { &mut *ptr::addr_of_mut!(QUEUE) };
+        let id = queue.next_id();
+        queue.insert(Entry {
+            id,
+            deadline,
+            period,
+            action,
+        })?;
+        queue.rearm();
+        Ok(id)
+    })
+}
+
+/// Cancels the timer `id`
+///
+/// Returns `false` if the timer has already expired, or was cancelled before.
+#[inline]
+pub fn cancel(id: TimerId) -> bool {
+    riscv::interrupt::free(|| {
+        // SAFETY: exclusive access in critical section
+        let queue = unsafe { &mut *ptr::addr_of_mut!(QUEUE) };
+        match (0..queue.len).find(|&idx| queue.entries[idx].is_some_and(|e| e.id == id)) {
+            Some(idx) => {
+                queue.remove(idx);
+                queue.rearm();
+                true
+            }
+            None => false,
+        }
+    })
+}
+
+/// Returns the number of scheduled timers
+#[inline]
+pub fn len() -> usize {
+    riscv::interrupt::free(|| unsafe { (*ptr::addr_of!(QUEUE)).len })
+}
+
+/// Fires expired timers and reprograms mtimecmp to the next deadline
+///
+/// Call this from the `MachineTimer` interrupt handler. Callbacks run with the
+/// queue unlocked, so they may schedule or cancel timers.
+#[inline]
+pub fn on_interrupt() {
+    loop {
+        let expired = riscv::interrupt::free(|| {
+            // SAFETY: exclusive access in critical section
+            let queue = unsafe { &mut *ptr::addr_of_mut!(QUEUE) };
+            if queue.is_head_expired() {
+                return Some(queue.pop());
+            }
+            // Rearming lowers the interrupt line. Another deadline may have
+            // passed in the meantime, so check again afterwards.
+            queue.rearm();
+            if queue.is_head_expired() {
+                Some(queue.pop())
+            } else {
+                None
+            }
+        });
+        match expired {
+            Some(entry) => entry.action.fire(),
+            None => break,
+        }
+    }
+}
@@ -0,0 +1,134 @@
+//! Owned peripheral singletons
+//!
+//! [Peripherals::take] hands out a token for each peripheral exactly once.
+//! Drivers are constructed by consuming the token, e.g.,
+//! [ApbUart::new](crate::uart::ApbUartHal::new) or
+//! [Timer::from](crate::timer_group::Timer), so that two drivers for the same
+//! peripheral cannot coexist without `unsafe`.
+//!
+//! The core-local interrupt controller is not owned. It is accessed through the
+//! static [Clic](crate::clic::Clic) API, whose register writes are `unsafe`
+//! where they may break a critical section.
+//!
+//! # Example
+//!
+//! ```ignore
+//! let p = Peripherals::take().unwrap();
+//! let mut serial = ApbUart::new(p.uart, CPU_FREQ, UartConfig::new(UART_BAUD));
+//! let mut mtimer = MTimer::new(p.mtimer);
+//! let mut timer0 = Timer::from(p.timer0);
+//! let pins = Gpio::new(p.gpio_lo, p.gpio_hi).split();
+//! ```
+use crate::mmap::{self, apb_timer};
+
+macro_rules! peripherals {
+    ($($(#[$meta:meta])* $field:ident: $name:ident = $base:expr,)*) => {
+        $(
+            $(#[$meta])*
+            #[allow(non_camel_case_types)]
+            pub struct $name {
+                _private: (),
+            }
+
+            impl $name {
+                /// Base address of the register block
+                pub const BASE: usize = $base;
+
+                /// # Safety
+                ///
+                /// Returns the peripheral regardless of whether it's already
+                /// owned elsewhere, allowing aliased access.
+                #[inline]
+                pub const unsafe fn steal() -> Self {
+                    Self { _private: () }
+                }
+            }
+        )*
+
+        /// All peripherals, sa. [Peripherals::take]
+        pub struct Peripherals {
+            $(
+                $(#[$meta])*
+                pub $field: $name,
+            )*
+        }
+
+        impl Peripherals {
+            /// # Safety
+            ///
+            /// Returns all peripherals regardless of whether they're already
+            /// owned elsewhere, allowing aliased access.
+            #[inline]
+            pub unsafe fn steal() -> Self {
+                TAKEN = true;
+                Self {
+                    $(
+                        $field: $name::steal(),
+                    )*
+                }
+            }
+        }
+    };
+}
+
+peripherals! {
+    /// APB UART, sa. [crate::uart]
+    uart: UART = mmap::UART_BASE,
+    /// GPIO pads 0..32, sa. [crate::gpio]
+    gpio_lo: GPIO_LO = mmap::gpio::GPIO_BASE,
+    /// GPIO pads 32..64, sa. [crate::gpio]
+    gpio_hi: GPIO_HI =
+        mmap::gpio::GPIO_BASE + core::mem::size_of::<mmap::gpio::PadRegisterBlock>(),
+    /// Machine timer, sa. [crate::mtimer]
+    mtimer: MTIMER = mmap::MTIMER_BASE,
+    /// APB timer 0, sa. [crate::timer_group]
+    timer0: TIMER0 = apb_timer::TIMER0_ADDR,
+    /// APB timer 1, sa. [crate::timer_group]
+    timer1: TIMER1 = apb_timer::TIMER1_ADDR,
+    /// APB timer 2, sa. [crate::timer_group]
+    timer2: TIMER2 = apb_timer::TIMER2_ADDR,
+    /// APB timer 3, sa. [crate::timer_group]
+    timer3: TIMER3 = apb_timer::TIMER3_ADDR,
+    /// SoC configuration registers
+    cfg: CFG = mmap::CFG_BASE,
+    /// APB SPI, sa. [crate::spi]
+    spi: SPI = mmap::SPI_BASE_ADDR,
+    /// NanoDMA, sa. [crate::dma]
+    dma: DMA = mmap::dma::DMA_BASE,
+}
+
+static mut TAKEN: bool = false;
+
+impl Peripherals {
+    /// Returns all peripherals on first call and `None` afterwards
+    #[inline]
+    pub fn take() -> Option<Self> {
+        riscv::interrupt::free(|| unsafe {
+            if TAKEN {
+                None
+            } else {
+                Some(Self::steal())
+            }
+        })
+    }
+}
+
+impl CFG {
+    /// Returns the peripheral clock divider
+    #[inline]
+    pub fn periph_clk_div(&self) -> u8 {
+        // SAFETY: valid, aligned address
+        unsafe { crate::read_u8_masked(Self::BASE + mmap::PERIPH_CLK_DIV_OFS, 0xf) }
+    }
+
+    /// Sets the peripheral clock divider
+    ///
+    /// # Safety
+    ///
+    /// * Changes the timing of configured peripherals, sa.
+    ///   [PERIPH_CLK_DIV_OFS](mmap::PERIPH_CLK_DIV_OFS).
+    #[inline]
+    pub unsafe fn set_periph_clk_div(&mut self, div: u8) {
+        crate::write_u8(Self::BASE + mmap::PERIPH_CLK_DIV_OFS, div & 0xf)
+    }
+}
@@ -108,6 +108,78 @@ pub mod menvcfg;
 pub mod menvcfgh
 */
 
+// # Counter registers
+
+pub mod mcycle {
+    //! Machine cycle counter, lower 32 bits, extended with writes
+    //!
+    //! [read64] is tear-free, i.e., it retries if the lower word carries into
+    //! the upper word in between the reads.
+
+    pub use riscv::register::mcycle::*;
+    use riscv::write_csr_as_usize;
+
+    // Writes the lower 32 bits
+    write_csr_as_usize!(0xB00);
+
+    /// Writes all 64 bits
+    ///
+    /// The lower word is zeroed first so that it can't carry into the upper
+    /// word in between the writes.
+    #[inline]
+    pub fn write64(value: u64) {
+        write(0);
+        super::mcycleh::write((value >> 32) as usize);
+        write(value as usize);
+    }
+}
+
+pub mod mcycleh {
+    //! Machine cycle counter, upper 32 bits, extended with writes
+
+    pub use riscv::register::mcycleh::*;
+    use riscv::write_csr_as_usize;
+
+    // Writes the upper 32 bits
+    write_csr_as_usize!(0xB80);
+}
+
+pub mod minstret {
+    //! Instructions-retired counter, lower 32 bits, extended with writes
+    //!
+    //! [read64] is tear-free, i.e., it retries if the lower word carries into
+    //! the upper word in between the reads.
+
+    pub use riscv::register::minstret::*;
+    use riscv::write_csr_as_usize;
+
+    // Writes the lower 32 bits
+    write_csr_as_usize!(0xB02);
+
+    /// Writes all 64 bits
+    ///
+    /// The lower word is zeroed first so that it can't carry into the upper
+    /// word in between the writes.
+    #[inline]
+    pub fn write64(value: u64) {
+        write(0);
+        super::minstreth::write((value >> 32) as usize);
+        write(value as usize);
+    }
+}
+
+pub mod minstreth {
+    //! Instructions-retired counter, upper 32 bits, extended with writes
+
+    pub use riscv::register::minstreth::*;
+    use riscv::write_csr_as_usize;
+
+    // Writes the upper 32 bits
+    write_csr_as_usize!(0xB82);
+}
+
+// # Counter registers end
+
 // # CLIC registers
 
 pub mod mnxti {
@@ -250,10 +322,16 @@ pub mod mintthresh {
     read_csr_as!(Mintthresh, 0x347);
 
     /// Writes the CSR and returns the previous value
+    ///
+    /// # Safety
+    ///
+    /// * Lowering the threshold breaks critical sections built on it, e.g.,
+    ///   [srp](crate::srp) locks and `critical-section-mintthresh`. Must not be
+    ///   lowered below the level such a critical section has raised it to.
     #[inline]
-    pub fn write(value: Mintthresh) -> usize {
+    pub unsafe fn write(value: Mintthresh) -> usize {
         let pvalue: usize;
-        unsafe { core::arch::asm!("csrrw {0}, 0x347, {1}", out(reg) pvalue, in(reg) value.bits) }
+        core::arch::asm!("csrrw {0}, 0x347, {1}", out(reg) pvalue, in(reg) value.bits);
         pvalue
     }
 
@@ -286,9 +364,6 @@ CSR_MSECCFG   = 12'h747,
 CSR_MSECCFGH  = 12'h757,
 
 // Debug trigger
-CSR_TSELECT   = 12'h7A0,
-CSR_TDATA1    = 12'h7A1,
-CSR_TDATA2    = 12'h7A2,
 CSR_TDATA3    = 12'h7A3,
 CSR_MCONTEXT  = 12'h7A8,
 CSR_MSCONTEXT = 12'h7AA,
@@ -301,6 +376,43 @@ CSR_DPC       = 12'h7b1,
 CSR_DSCRATCH0 = 12'h7b2, // optional
 CSR_DSCRATCH1 = 12'h7b3, // optional
 */
+
+pub mod tselect {
+    //! Trigger select register
+    //!
+    //! Selects which trigger is accessed through [tdata1](super::tdata1) and
+    //! [tdata2](super::tdata2). WARL, i.e., reads back a different value if the
+    //! trigger is not implemented.
+
+    use riscv::{read_csr_as_usize, write_csr_as_usize};
+
+    // Supported operations
+    read_csr_as_usize!(0x7A0);
+    write_csr_as_usize!(0x7A0);
+}
+
+pub mod tdata1 {
+    //! Trigger data 1 register, i.e., type & configuration of the selected
+    //! trigger
+
+    use riscv::{read_csr_as_usize, write_csr_as_usize};
+
+    // Supported operations
+    read_csr_as_usize!(0x7A1);
+    write_csr_as_usize!(0x7A1);
+}
+
+pub mod tdata2 {
+    //! Trigger data 2 register, i.e., trigger-specific data of the selected
+    //! trigger
+
+    use riscv::{read_csr_as_usize, write_csr_as_usize};
+
+    // Supported operations
+    read_csr_as_usize!(0x7A2);
+    write_csr_as_usize!(0x7A2);
+}
+
 // # Debug registers end
 
 pub mod cpuctrlsts {
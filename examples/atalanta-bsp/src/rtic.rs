@@ -0,0 +1,159 @@
+//! Hardware backend for RTIC 2 on the CLIC
+//!
+//! This is the runtime half of a backend only. RTIC's code generation lives in
+//! `rtic-macros`, which selects a backend per target; Atalanta is not one of
+//! them, so `#[rtic::app]` cannot target this module yet. Until a matching
+//! codegen backend exists, applications call into it by hand, e.g., from
+//! handlers bound with [nested_interrupt](crate::nested_interrupt).
+//!
+//! The runtime is modelled after RTIC's RISC-V backends:
+//!
+//! * Task priorities map one-to-one to CLIC interrupt levels, with 8 level bits
+//!   configured by [init]. Priority 0 is thread mode.
+//! * Preemption is level-based. Tasks must be bound with a nesting trap entry,
+//!   i.e., [generate_nested_trap_entry](crate::generate_nested_trap_entry), or
+//!   [generate_pcs_trap_entry](crate::generate_pcs_trap_entry) for tasks that
+//!   opt into PCS.
+//! * Software tasks are run by dispatchers: unused interrupt lines from
+//!   [DISPATCHERS], pended with [pend].
+//! * Resources are locked like [srp](crate::srp) resources: by raising
+//!   `mintthresh` to the ceiling, or by disabling interrupts globally for a
+//!   ceiling of [MAX_PRIORITY].
+//!
+//! [Peripherals] is the `device` handed to `init`, see
+//! [peripherals](crate::peripherals) for the owned singletons.
+use crate::{
+    clic::{Clic, Polarity, Trig},
+    interrupt::restore_threshold,
+    register::{mintstatus, mintthresh},
+    srp::{self, with_ceiling},
+    Interrupt,
+};
+
+pub use crate::Peripherals;
+
+/// Highest task priority. Resources at this ceiling are locked by disabling
+/// interrupts globally.
+pub const MAX_PRIORITY: u8 = srp::MAX_LEVEL;
+
+/// Interrupt lines available as dispatchers for software tasks
+///
+/// These are DMA completion lines not raised by the hardware. `Dma0` is
+/// reserved for [crate::dma].
+pub const DISPATCHERS: &[Interrupt] = &[
+    Interrupt::Dma1,
+    Interrupt::Dma2,
+    Interrupt::Dma3,
+    Interrupt::Dma4,
+    Interrupt::Dma5,
+    Interrupt::Dma6,
+    Interrupt::Dma7,
+    Interrupt::Dma8,
+    Interrupt::Dma9,
+    Interrupt::Dma10,
+    Interrupt::Dma11,
+    Interrupt::Dma12,
+    Interrupt::Dma13,
+    Interrupt::Dma14,
+    Interrupt::Dma15,
+];
+
+/// Returns the CLIC interrupt level of a task at `priority`
+#[inline(always)]
+pub const fn level(priority: u8) -> u8 {
+    priority
+}
+
+/// Prepares the CLIC for RTIC: 8 level bits and the lowest threshold
+///
+/// # Safety
+///
+/// * Must be called before enabling interrupts.
+#[inline]
+pub unsafe fn init() {
+    Clic::smclicconfig().set_mnlbits(8);
+    mintthresh::write(0x0.into());
+}
+
+/// Runs the task `f` at `priority`
+///
+/// Restores the interrupt threshold after `f`, so that a task never returns
+/// with the threshold raised. `priority` must match the active interrupt
+/// level, which is checked in debug builds.
+#[inline(always)]
+pub fn run<F: FnOnce()>(priority: u8, f: F) {
+    debug_assert_eq!(
+        mintstatus::read().mil(),
+        level(priority) as usize,
+        "task priority does not match the active interrupt level"
+    );
+    if priority == 0 {
+        f();
+    } else {
+        let prev = mintthresh::read().bits() as u8;
+        f();
+        restore_threshold(prev);
+    }
+}
+
+/// Runs `f` with exclusive access to the resource at `ptr` with ceiling
+/// `ceiling`
+///
+/// # Safety
+///
+/// * `ptr` must point to a resource shared by tasks at or below `ceiling` only.
+/// * Must be called from a task at or below `ceiling`.
+#[inline(always)]
+pub unsafe fn lock<T, R>(ptr: *mut T, ceiling: u8, f: impl FnOnce(&mut T) -> R) -> R {
+    with_ceiling(level(ceiling), || f(&mut *ptr))
+}
+
+/// Pends the dispatcher or bound interrupt `int`
+#[inline]
+pub fn pend(int: Interrupt) {
+    // SAFETY: dispatchers are edge-triggered, software writes are supported
+    unsafe { Clic::ip(int).pend() }
+}
+
+/// Unpends the dispatcher or bound interrupt `int`
+#[inline]
+pub fn unpend(int: Interrupt) {
+    // SAFETY: dispatchers are edge-triggered, software writes are supported
+    unsafe { Clic::ip(int).unpend() }
+}
+
+/// Enables dispatcher `int` at `priority`
+///
+/// Dispatchers are edge-triggered, so that [pend] is latched.
+///
+/// # Safety
+///
+/// * Enabling an interrupt source can break mask-based critical sections.
+#[inline]
+pub unsafe fn enable_dispatcher(int: Interrupt, priority: u8, pcs: bool) {
+    Clic::configure(int)
+        .trigger(Trig::Edge)
+        .polarity(Polarity::Pos)
+        .shv(true)
+        .level(level(priority))
+        .pcs(pcs)
+        .enable()
+        .keep();
+}
+
+/// Enables the interrupt `int` bound to a hardware task at `priority`
+///
+/// The trigger type & polarity are left as configured by the driver.
+///
+/// # Safety
+///
+/// * Enabling an interrupt source can break mask-based critical sections.
+#[inline]
+pub unsafe fn enable_bound(int: Interrupt, priority: u8, pcs: bool) {
+    Clic::configure(int)
+        .shv(true)
+        .level(level(priority))
+        .pcs(pcs)
+        .enable()
+        .keep();
+}
@@ -0,0 +1,256 @@
+//! Global output sink for [sprint](crate::sprint) and
+//! [sprintln](crate::sprintln)
+//!
+//! Output is written to [ApbUart] once it has been initialized, e.g., with
+//! [ApbUart::new](crate::uart::ApbUartHal::new), unless another sink has been
+//! registered with [set_sink]. Output is discarded before either happens. The
+//! sink is claimed for the duration of a single `sprint!` or `sprintln!`, so
+//! output from interrupt handlers doesn't tear it. Output attempted while the
+//! sink is claimed is discarded, sa. [with_sink].
+//!
+//! # Example
+//!
+//! ```ignore
+//! static mut CAPTURE: RamSink<256> = RamSink::new();
+//!
+//! // Capture output in RAM, e.g., to keep it off the wire during a test
+//! sink::set_sink(unsafe { &mut *ptr::addr_of_mut!(CAPTURE) });
+//! sprintln!("captured");
+//! // Revert to the default UART
+//! sink::take_sink();
+//! ```
+use core::{ptr, sync::atomic::Ordering};
+
+use crate::uart::{ApbUart, ApbUartHal, UART_IS_INIT};
+
+/// Destination for formatted output
+///
+/// Output is infallible from the point of view of the caller. A sink that
+/// cannot make progress should discard the output.
+pub trait Sink {
+    fn write_str(&mut self, s: &str);
+}
+
+impl<const BASE_ADDR: usize> Sink for ApbUartHal<BASE_ADDR> {
+    #[inline]
+    fn write_str(&mut self, s: &str) {
+        ApbUartHal::write_str(self, s)
+    }
+}
+
+/// Discards all output
+pub struct NullSink;
+
+impl Sink for NullSink {
+    #[inline]
+    fn write_str(&mut self, _s: &str) {}
+}
+
+/// Captures output into a RAM buffer
+///
+/// Output that doesn't fit in the buffer is discarded.
+pub struct RamSink<const N: usize> {
+    buf: [u8; N],
+    len: usize,
+}
+
+impl<const N: usize> RamSink<N> {
+    pub const fn new() -> Self {
+        Self {
+            buf: [0; N],
+            len: 0,
+        }
+    }
+
+    /// Returns the captured output
+    #[inline]
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.buf[..self.len]
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns `true` if output has been discarded
+    #[inline]
+    pub fn is_full(&self) -> bool {
+        self.len == N
+    }
+
+    #[inline]
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+}
+
+impl<const N: usize> Default for RamSink<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> Sink for RamSink<N> {
+    #[inline]
+    fn write_str(&mut self, s: &str) {
+        let n = s.len().min(N - self.len);
+        self.buf[self.len..self.len + n].copy_from_slice(&s.as_bytes()[..n]);
+        self.len += n;
+    }
+}
+
+static mut SINK: Option<&'static mut dyn Sink> = None;
+/// Set while [with_sink] holds the sink
+static mut BUSY: bool = false;
+/// Incremented on each [set_sink] and [take_sink], so that [with_sink] doesn't
+/// restore a sink that was replaced while it was held
+static mut EPOCH: u32 = 0;
+
+/// Registers `sink` as the destination for all formatted output, returning the
+/// previously registered sink
+///
+/// Call this once at init, or whenever output needs to be redirected. Returns
+/// `None` if called while the previous sink is held by [with_sink], e.g., from
+/// an interrupt handler.
+#[inline]
+pub fn set_sink(sink: &'static mut dyn Sink) -> Option<&'static mut dyn Sink> {
+    riscv::interrupt::free(|| unsafe {
+        EPOCH = EPOCH.wrapping_add(1);
+        (*ptr::addr_of_mut!(SINK)).replace(sink)
+    })
+}
+
+/// Unregisters the current sink, reverting output to [ApbUart]
+///
+/// Returns `None` if called while the sink is held by [with_sink].
+#[inline]
+pub fn take_sink() -> Option<&'static mut dyn Sink> {
+    riscv::interrupt::free(|| unsafe {
+        EPOCH = EPOCH.wrapping_add(1);
+        (*ptr::addr_of_mut!(SINK)).take()
+    })
+}
+
+/// Runs `f` on the registered sink
+///
+/// Falls back to [ApbUart] if no sink has been registered, or to [NullSink]
+/// if the UART has not been initialized either. Only claiming and
+/// releasing the sink is guarded by a critical section, so interrupts are
+/// serviced while `f` writes. Nested calls, e.g., `sprint!` from an interrupt
+/// handler that preempted `f`, or from `f` itself, cannot access the claimed
+/// sink and run `f` on [NullSink] instead, discarding their output.
+#[inline]
+pub fn with_sink<R>(f: impl FnOnce(&mut dyn Sink) -> R) -> R {
+    let claimed = riscv::interrupt::free(|| unsafe {
+        if BUSY {
+            None
+        } else {
+            BUSY = true;
+            Some(((*ptr::addr_of_mut!(SINK)).take(), EPOCH))
+        }
+    });
+    let Some((mut sink, epoch)) = claimed else {
+        return f(&mut NullSink);
+    };
+
+    let result = match sink.as_deref_mut() {
+        Some(sink) => f(sink),
+        // SAFETY: the UART is initialized, and writes to it are serialized by the
+        // claim
+        None if UART_IS_INIT.load(Ordering::Relaxed) => f(&mut unsafe { ApbUart::instance() }),
+        None => f(&mut NullSink),
+    };
+
+    riscv::interrupt::free(|| unsafe {
+        // Don't restore the sink over one registered while it was held
+        if EPOCH == epoch {
+            *ptr::addr_of_mut!(SINK) = sink;
+        }
+        BUSY = false;
+    });
+    result
+}
+
+/// Adapts a [Sink] to [core::fmt::Write] and `ufmt::uWrite`
+pub struct SinkWriter<'a>(pub &'a mut dyn Sink);
+
+impl core::fmt::Write for SinkWriter<'_> {
+    #[inline]
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        self.0.write_str(s);
+        Ok(())
+    }
+}
+
+#[cfg(feature = "ufmt")]
+impl ufmt::uWrite for SinkWriter<'_> {
+    type Error = core::convert::Infallible;
+
+    #[inline]
+    fn write_str(&mut self, s: &str) -> Result<(), Self::Error> {
+        self.0.write_str(s);
+        Ok(())
+    }
+}
+
+/// [log] implementation that writes records to the registered sink
+#[cfg(feature = "log")]
+pub struct SinkLogger;
+
+#[cfg(feature = "log")]
+static LOGGER: SinkLogger = SinkLogger;
+
+#[cfg(feature = "log")]
+impl log::Log for SinkLogger {
+    #[inline]
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &log::Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        with_sink(|sink| {
+            use core::fmt::Write;
+            let _ = write!(
+                SinkWriter(sink),
+                "[{} {}] {}\r\n",
+                record.level(),
+                record.target(),
+                record.args()
+            );
+        })
+    }
+
+    fn flush(&self) {}
+}
+
+/// Installs [SinkLogger] as the global [log] logger, discarding records less
+/// severe than `level`
+///
+/// # Safety
+///
+/// * Must not race with other calls to [init_logger] or [set_log_level], e.g.,
+///   call once at init before enabling interrupts. The target has no
+///   compare-and-swap, so `log` can only be initialized racily.
+#[cfg(feature = "log")]
+#[inline]
+pub unsafe fn init_logger(level: log::LevelFilter) -> Result<(), log::SetLoggerError> {
+    log::set_logger_racy(&LOGGER)?;
+    log::set_max_level_racy(level);
+    Ok(())
+}
+
+/// Changes the level filter of the global [log] logger
+#[cfg(feature = "log")]
+#[inline]
+pub fn set_log_level(level: log::LevelFilter) {
+    riscv::interrupt::free(|| unsafe { log::set_max_level_racy(level) })
+}
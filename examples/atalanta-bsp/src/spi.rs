@@ -0,0 +1,519 @@
+//! Implementation of [PULP APB SPI master](https://github.com/pulp-platform/apb_spi_master)
+//!
+//! The controller runs half-duplex transactions consisting of an optional
+//! command, an optional address, optional dummy cycles and a data phase that
+//! either transmits or receives. Chip select is driven by hardware and is only
+//! held for the duration of a single transaction.
+//!
+//! The end of each transaction is signaled by a pulse on
+//! [Interrupt::SpiEotIrq], which is latched by the CLIC as an edge-triggered
+//! interrupt. Small transfers that fit the FIFOs can be dispatched without
+//! blocking using [ApbSpiHal::start_write] and [ApbSpiHal::start_read].
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use embedded_hal::spi::{self, Operation, SpiBus, SpiDevice};
+
+use crate::{
+    clic::{Clic, Polarity, Trig},
+    mmap::*,
+    peripherals::SPI,
+    read_u32, read_u8_masked, write_u32, Interrupt,
+};
+
+/// Set by [on_interrupt] when the interrupt handler observes end of
+/// transmission
+static EOT: AtomicBool = AtomicBool::new(false);
+
+/// The interrupt line raised on end of transmission
+pub const SPI_EOT_IRQ: Interrupt = Interrupt::SpiEotIrq;
+
+/// Maximum number of bytes in a single hardware transaction
+pub const MAX_TRANSACTION_LEN: usize = (SPI_LEN_DATA_MASK as usize / 8) & !0b11;
+
+/// Maximum number of bytes that fit in the FIFOs, i.e., that can be
+/// transferred without the CPU refilling or draining the FIFO
+pub const FIFO_LEN: usize = SPI_FIFO_DEPTH as usize * 4;
+
+/// Hardware chip select line
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[repr(u8)]
+pub enum ChipSelect {
+    Cs0 = 0,
+    Cs1 = 1,
+    Cs2 = 2,
+    Cs3 = 3,
+}
+
+/// Number of data lines used for the data phase
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DataMode {
+    /// Single data line per direction (MOSI/MISO)
+    Standard,
+    /// Four bidirectional data lines
+    Quad,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SpiError {
+    /// Buffer does not fit the FIFO, sa. [FIFO_LEN], or a single hardware
+    /// transaction, sa. [MAX_TRANSACTION_LEN]
+    TooLong,
+    /// The controller is half-duplex and cannot read and write at once
+    FullDuplex,
+    /// The sequence of operations cannot be run in a single hardware
+    /// transaction, i.e., with chip select held asserted
+    Unsupported,
+}
+
+impl spi::Error for SpiError {
+    fn kind(&self) -> spi::ErrorKind {
+        spi::ErrorKind::Other
+    }
+}
+
+/// Relocatable HAL driver for PULP APB SPI master
+///
+/// The type parameter represents the base address for the SPI master.
+pub struct ApbSpiHal<const BASE_ADDR: usize> {
+    cs: ChipSelect,
+    mode: DataMode,
+    /// Command & address lengths for the next transaction, sa. [SPI_LEN_OFS]
+    header: u32,
+}
+
+/// [ApbSpiHal]
+pub type ApbSpi = ApbSpiHal<SPI_BASE_ADDR>;
+
+impl ApbSpi {
+    /// Initializes the SPI owned by `spi`, sa. [ApbSpiHal::init]
+    #[inline]
+    pub fn new(_spi: SPI, freq: u32, sck: u32) -> Self {
+        // SAFETY: the token proves ownership of the SPI
+        unsafe { Self::init(freq, sck) }
+    }
+}
+
+impl<const BASE_ADDR: usize> ApbSpiHal<BASE_ADDR> {
+    /// # Parameters
+    ///
+    /// * `freq` - SoC frequency, used to calculate the SPI clock divider
+    /// * `sck` - target SPI clock frequency. The resulting frequency is rounded
+    ///   down to the nearest achievable value.
+    ///
+    /// # Safety
+    ///
+    /// * Bypasses the ownership tokens of [Peripherals](crate::Peripherals). No
+    ///   other driver for the SPI at `BASE_ADDR` may be in use. Prefer
+    ///   [ApbSpi::new].
+    #[inline]
+    pub unsafe fn init(freq: u32, sck: u32) -> Self {
+        let mut spi = Self {
+            cs: ChipSelect::Cs0,
+            mode: DataMode::Standard,
+            header: 0,
+        };
+
+        // Clear FIFOs & controller state
+        write_u32(BASE_ADDR + SPI_STATUS_OFS, SPI_STATUS_SWRST_BIT);
+        write_u32(BASE_ADDR + SPI_STATUS_OFS, 0);
+        write_u32(BASE_ADDR + SPI_DUMMY_OFS, 0);
+        write_u32(BASE_ADDR + SPI_INTCFG_OFS, 0);
+
+        // Read current peripheral clock divider
+        let periph_clk_div = unsafe { read_u8_masked(CFG_BASE + PERIPH_CLK_DIV_OFS, 0xf) }.max(1);
+        let periph_freq = freq / periph_clk_div as u32;
+        let div = periph_freq.div_ceil(2 * sck).saturating_sub(1);
+        spi.set_clk_div(div.min(SPI_CLKDIV_MASK) as u8);
+
+        Clic::attr(SPI_EOT_IRQ).set_trig(Trig::Edge);
+        Clic::attr(SPI_EOT_IRQ).set_polarity(Polarity::Pos);
+        // SAFETY: EOT interrupt is an edge-triggered line, software writes are
+        // supported
+        unsafe { Clic::ip(SPI_EOT_IRQ).unpend() };
+        EOT.store(false, Ordering::Relaxed);
+
+        spi
+    }
+
+    /// # Safety
+    ///
+    /// Returns a potentially uninitialized instance of APB SPI master. Make
+    /// sure to call [ApbSpiHal::init] prior to this call, otherwise the SPI
+    /// won't behave properly.
+    #[inline]
+    pub const unsafe fn instance() -> Self {
+        Self {
+            cs: ChipSelect::Cs0,
+            mode: DataMode::Standard,
+            header: 0,
+        }
+    }
+
+    /// Sets the SPI clock to peripheral clock divided by `2 * (div + 1)`
+    #[inline]
+    pub fn set_clk_div(&mut self, div: u8) {
+        write_u32(BASE_ADDR + SPI_CLKDIV_OFS, div as u32);
+    }
+
+    /// Selects the chip select line asserted during following transactions
+    #[inline]
+    pub fn select(&mut self, cs: ChipSelect) {
+        self.cs = cs;
+    }
+
+    /// Selects the number of data lines used in following transactions
+    #[inline]
+    pub fn set_data_mode(&mut self, mode: DataMode) {
+        self.mode = mode;
+    }
+
+    /// Sets the command and address sent at the start of the next transaction
+    ///
+    /// Both values are given right-aligned and transmitted MSB first. A length
+    /// of zero omits the phase. Lengths are given in bits and must be less
+    /// than or equal to 32. The setting is cleared once the next transaction
+    /// is started.
+    #[inline]
+    pub fn set_command(&mut self, cmd: u32, cmd_bits: u8, addr: u32, addr_bits: u8) {
+        debug_assert!(cmd_bits <= 32 && addr_bits <= 32);
+
+        // The controller transmits from the MSB, align values accordingly
+        write_u32(
+            BASE_ADDR + SPI_CMD_OFS,
+            cmd.checked_shl(32 - cmd_bits as u32).unwrap_or(0),
+        );
+        write_u32(
+            BASE_ADDR + SPI_ADDR_OFS,
+            addr.checked_shl(32 - addr_bits as u32).unwrap_or(0),
+        );
+        self.header = ((cmd_bits as u32 & SPI_LEN_CMD_MASK) << SPI_LEN_CMD_BIT_IDX)
+            | ((addr_bits as u32 & SPI_LEN_ADDR_MASK) << SPI_LEN_ADDR_BIT_IDX);
+    }
+
+    /// Sets the number of dummy cycles between the address and the data phase
+    #[inline]
+    pub fn set_dummy_cycles(&mut self, read: u16, write: u16) {
+        write_u32(
+            BASE_ADDR + SPI_DUMMY_OFS,
+            ((read as u32) << SPI_DUMMY_RD_BIT_IDX) | ((write as u32) << SPI_DUMMY_WR_BIT_IDX),
+        );
+    }
+
+    /// Writes `data` and blocks until transmission is complete
+    pub fn write_blocking(&mut self, data: &[u8]) {
+        for chunk in data.chunks(MAX_TRANSACTION_LEN) {
+            self.start_transaction(self.write_op(), chunk.len());
+            for word in chunk.chunks(4) {
+                while self.tx_elements() >= SPI_FIFO_DEPTH {}
+                write_u32(BASE_ADDR + SPI_TXFIFO_OFS, pack_word(word));
+            }
+            self.wait_idle();
+        }
+    }
+
+    /// Reads into `buf` and blocks until reception is complete
+    pub fn read_blocking(&mut self, buf: &mut [u8]) {
+        for chunk in buf.chunks_mut(MAX_TRANSACTION_LEN) {
+            self.start_transaction(self.read_op(), chunk.len());
+            for word in chunk.chunks_mut(4) {
+                while self.rx_elements() == 0 {}
+                unpack_word(read_u32(BASE_ADDR + SPI_RXFIFO_OFS), word);
+            }
+            self.wait_idle();
+        }
+    }
+
+    /// Dispatches a write of `data` without blocking
+    ///
+    /// `data` must fit the TX FIFO, sa. [FIFO_LEN]. Use [Self::is_done] or the
+    /// [SPI_EOT_IRQ] interrupt to find out when the transmission has completed.
+    #[inline]
+    pub fn start_write(&mut self, data: &[u8]) -> Result<(), SpiError> {
+        if data.len() > FIFO_LEN {
+            return Err(SpiError::TooLong);
+        }
+
+        self.arm_eot();
+        // Fill FIFO first to make sure the controller does not stall on data
+        for word in data.chunks(4) {
+            write_u32(BASE_ADDR + SPI_TXFIFO_OFS, pack_word(word));
+        }
+        self.start_transaction(self.write_op(), data.len());
+        Ok(())
+    }
+
+    /// Dispatches a read of `len` bytes without blocking
+    ///
+    /// `len` must fit the RX FIFO, sa. [FIFO_LEN]. Use [Self::is_done] or the
+    /// [SPI_EOT_IRQ] interrupt to find out when the reception has completed,
+    /// then collect the data using [Self::read_fifo].
+    #[inline]
+    pub fn start_read(&mut self, len: usize) -> Result<(), SpiError> {
+        if len > FIFO_LEN {
+            return Err(SpiError::TooLong);
+        }
+
+        self.arm_eot();
+        self.start_transaction(self.read_op(), len);
+        Ok(())
+    }
+
+    /// Collects the data of a completed [Self::start_read] into `buf`
+    #[inline]
+    pub fn read_fifo(&mut self, buf: &mut [u8]) {
+        for word in buf.chunks_mut(4) {
+            unpack_word(read_u32(BASE_ADDR + SPI_RXFIFO_OFS), word);
+        }
+    }
+
+    /// Returns `true` when the hardware has signaled end of transmission
+    /// since the last transaction was dispatched
+    #[inline]
+    pub fn is_done(&self) -> bool {
+        if EOT.load(Ordering::Relaxed) {
+            return true;
+        }
+
+        // Interrupt not serviced, check the latched end of transmission pulse
+        // instead
+        // SAFETY: SPI_EOT_IRQ is configured as edge-triggered in `init`
+        if unsafe { Clic::ip(SPI_EOT_IRQ).is_pending() } {
+            unsafe { Clic::ip(SPI_EOT_IRQ).unpend() };
+            EOT.store(true, Ordering::Relaxed);
+            return true;
+        }
+
+        false
+    }
+
+    /// Raise [SPI_EOT_IRQ] with interrupt level `level` on end of transmission
+    ///
+    /// Call [on_interrupt] from the `SpiEotIrq` interrupt handler to let the
+    /// driver know the transaction has completed.
+    ///
+    /// # Safety
+    ///
+    /// * Enabling an interrupt source can break mask-based critical sections.
+    #[inline]
+    pub unsafe fn listen(&mut self, level: u8) {
+        Clic::attr(SPI_EOT_IRQ).set_shv(true);
+        Clic::ctl(SPI_EOT_IRQ).set_level(level);
+        Clic::ie(SPI_EOT_IRQ).enable();
+    }
+
+    /// Stop raising [SPI_EOT_IRQ] on end of transmission
+    #[inline]
+    pub fn unlisten(&mut self) {
+        Clic::ie(SPI_EOT_IRQ).disable();
+    }
+
+    #[inline]
+    fn arm_eot(&mut self) {
+        EOT.store(false, Ordering::Relaxed);
+        // SAFETY: EOT interrupt is an edge-triggered line, software writes are
+        // supported
+        unsafe { Clic::ip(SPI_EOT_IRQ).unpend() };
+    }
+
+    #[inline]
+    fn start_transaction(&mut self, op: u32, len: usize) {
+        let data_bits = (len as u32 * 8) & SPI_LEN_DATA_MASK;
+        write_u32(
+            BASE_ADDR + SPI_LEN_OFS,
+            (data_bits << SPI_LEN_DATA_BIT_IDX) | self.header,
+        );
+        write_u32(
+            BASE_ADDR + SPI_STATUS_OFS,
+            (0b1 << (SPI_STATUS_CS_BIT_IDX + self.cs as u32)) | op,
+        );
+        // Command & address are one-shot
+        self.header = 0;
+    }
+
+    #[inline]
+    fn read_op(&self) -> u32 {
+        match self.mode {
+            DataMode::Standard => SPI_STATUS_RD_BIT,
+            DataMode::Quad => SPI_STATUS_QRD_BIT,
+        }
+    }
+
+    #[inline]
+    fn write_op(&self) -> u32 {
+        match self.mode {
+            DataMode::Standard => SPI_STATUS_WR_BIT,
+            DataMode::Quad => SPI_STATUS_QWR_BIT,
+        }
+    }
+
+    #[inline]
+    fn tx_elements(&self) -> u32 {
+        (read_u32(BASE_ADDR + SPI_STATUS_OFS) >> SPI_STATUS_TX_ELEMS_BIT_IDX)
+            & SPI_STATUS_ELEMS_MASK
+    }
+
+    #[inline]
+    fn rx_elements(&self) -> u32 {
+        (read_u32(BASE_ADDR + SPI_STATUS_OFS) >> SPI_STATUS_RX_ELEMS_BIT_IDX)
+            & SPI_STATUS_ELEMS_MASK
+    }
+
+    #[inline]
+    fn wait_idle(&self) {
+        while self.tx_elements() != 0 {}
+        while read_u32(BASE_ADDR + SPI_STATUS_OFS) & SPI_STATUS_STATE_MASK != SPI_STATUS_STATE_IDLE
+        {
+        }
+    }
+}
+
+/// Packs up to 4 bytes into a word, first byte in the MSBs
+#[inline]
+fn pack_word(bytes: &[u8]) -> u32 {
+    let mut word = [0u8; 4];
+    word[..bytes.len()].copy_from_slice(bytes);
+    u32::from_be_bytes(word)
+}
+
+/// Packs up to 4 bytes into a word, last byte in the LSBs
+#[inline]
+fn pack_word_right(bytes: &[u8]) -> u32 {
+    pack_word(bytes)
+        .checked_shr(8 * (4 - bytes.len() as u32))
+        .unwrap_or(0)
+}
+
+/// Unpacks a received word into up to 4 bytes
+///
+/// A partially filled word holds the received bits in the LSBs.
+#[inline]
+fn unpack_word(word: u32, bytes: &mut [u8]) {
+    let word = word << (8 * (4 - bytes.len()));
+    bytes.copy_from_slice(&word.to_be_bytes()[..bytes.len()]);
+}
+
+/// Signals end of transmission to the driver
+///
+/// Call this from the `SpiEotIrq` interrupt handler when using
+/// [ApbSpiHal::listen].
+#[inline]
+pub fn on_interrupt() {
+    EOT.store(true, Ordering::Relaxed);
+}
+
+impl<const BASE_ADDR: usize> spi::ErrorType for ApbSpiHal<BASE_ADDR> {
+    type Error = SpiError;
+}
+
+/// Blocking bus access
+///
+/// The controller is half-duplex: it either transmits or receives during a
+/// transaction, never both. Hence [SpiBus::transfer] accepts only one non-empty
+/// buffer, and [SpiBus::transfer_in_place] fails with [SpiError::FullDuplex]
+/// for any non-empty buffer.
+impl<const BASE_ADDR: usize> SpiBus<u8> for ApbSpiHal<BASE_ADDR> {
+    #[inline]
+    fn read(&mut self, words: &mut [u8]) -> Result<(), Self::Error> {
+        self.read_blocking(words);
+        Ok(())
+    }
+
+    #[inline]
+    fn write(&mut self, words: &[u8]) -> Result<(), Self::Error> {
+        self.write_blocking(words);
+        Ok(())
+    }
+
+    /// The controller is half-duplex, only one of `read` and `write` may be
+    /// non-empty
+    #[inline]
+    fn transfer(&mut self, read: &mut [u8], write: &[u8]) -> Result<(), Self::Error> {
+        match (read.is_empty(), write.is_empty()) {
+            (_, true) => self.read_blocking(read),
+            (true, false) => self.write_blocking(write),
+            (false, false) => return Err(SpiError::FullDuplex),
+        }
+        Ok(())
+    }
+
+    /// The controller is half-duplex, `words` must be empty
+    #[inline]
+    fn transfer_in_place(&mut self, words: &mut [u8]) -> Result<(), Self::Error> {
+        if words.is_empty() {
+            Ok(())
+        } else {
+            Err(SpiError::FullDuplex)
+        }
+    }
+
+    #[inline]
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        self.wait_idle();
+        Ok(())
+    }
+}
+
+/// Runs a transaction on the selected chip select line, sa. [ApbSpiHal::select]
+///
+/// Chip select is held only for the duration of a single hardware transaction,
+/// which limits the supported sequences of operations to:
+///
+/// * a single `Read` or `Write`
+/// * a `Write` of at most 8 bytes followed by a `Read` or a `Write`. The first
+///   write is sent as the command & address phase. If the second operation is
+///   empty, the command & address phase is sent on its own.
+///
+/// The data phase is limited to [MAX_TRANSACTION_LEN] bytes, longer operations
+/// return [SpiError::TooLong].
+impl<const BASE_ADDR: usize> SpiDevice<u8> for ApbSpiHal<BASE_ADDR> {
+    fn transaction(&mut self, operations: &mut [Operation<'_, u8>]) -> Result<(), Self::Error> {
+        let data = match operations {
+            [] => return Ok(()),
+            [data] => data,
+            [Operation::Write(header), data] if header.len() <= 8 => {
+                let (cmd, addr) = header.split_at(header.len().min(4));
+                self.set_command(
+                    pack_word_right(cmd),
+                    8 * cmd.len() as u8,
+                    pack_word_right(addr),
+                    8 * addr.len() as u8,
+                );
+                data
+            }
+            _ => return Err(SpiError::Unsupported),
+        };
+
+        let data_len = match data {
+            Operation::Read(buf) => Some(buf.len()),
+            Operation::Write(buf) => Some(buf.len()),
+            Operation::Transfer(read, write) => Some(read.len().max(write.len())),
+            Operation::TransferInPlace(buf) => Some(buf.len()),
+            Operation::DelayNs(_) => None,
+        };
+        if data_len.is_some_and(|len| len > MAX_TRANSACTION_LEN) {
+            // Chip select would be released between chunks
+            self.header = 0;
+            return Err(SpiError::TooLong);
+        }
+        if data_len == Some(0) {
+            // Nothing would be sent for an empty data phase, send the header
+            // alone so that it isn't dropped
+            if self.header != 0 {
+                self.start_transaction(self.write_op(), 0);
+                self.wait_idle();
+            }
+            return Ok(());
+        }
+
+        let result = match data {
+            Operation::Read(buf) => SpiBus::read(self, buf),
+            Operation::Write(buf) => SpiBus::write(self, buf),
+            Operation::Transfer(read, write) => SpiBus::transfer(self, read, write),
+            Operation::TransferInPlace(buf) => SpiBus::transfer_in_place(self, buf),
+            Operation::DelayNs(_) => Err(SpiError::Unsupported),
+        };
+        // Don't leak the command & address into the next transaction on failure
+        self.header = 0;
+        result
+    }
+}
@@ -0,0 +1,161 @@
+//! Stack Resource Policy (SRP) resource sharing on top of CLIC levels
+//!
+//! Tasks are interrupt handlers running at a static CLIC level, or thread mode
+//! at level 0. A [Resource] is declared with a ceiling, the highest level of
+//! any task that accesses it. Locking a resource raises the interrupt
+//! threshold to the ceiling, so that no other task sharing it can preempt the
+//! lock holder, while tasks above the ceiling keep running.
+//!
+//! A task proves its level with a [Task] token. Locking a resource from a task
+//! above its ceiling is rejected at compile time. The token tracks the
+//! threshold raised by the locks it holds, so locks nest: locking a resource
+//! whose ceiling is already covered costs nothing. Locking the same resource
+//! again from within its own lock panics.
+//!
+//! # Example
+//!
+//! ```ignore
+//! static COUNTER: Resource<u32, 0x80> = Resource::new(0);
+//!
+//! // Handler configured at level 0x40 & nested, sa. [crate::nested_interrupt]
+//! #[nested_interrupt]
+//! fn Timer0Cmp() {
+//!     let task = unsafe { Task::<0x40>::new() };
+//!     COUNTER.lock(&task, |c| *c += 1);
+//! }
+//!
+//! // Handler configured at level 0x80, the ceiling of COUNTER
+//! #[nested_interrupt]
+//! fn Timer1Cmp() {
+//!     let task = unsafe { Task::<0x80>::new() };
+//!     COUNTER.lock(&task, |c| *c += 1);
+//! }
+//! ```
+use core::{
+    cell::{Cell, UnsafeCell},
+    marker::PhantomData,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use crate::interrupt::with_threshold;
+
+/// Highest interrupt level. Locks at this ceiling disable interrupts globally.
+pub const MAX_LEVEL: u8 = 0xff;
+
+/// Proof of running in a task at interrupt level `LEVEL`
+///
+/// Not `Send`, i.e., it cannot be handed over to another task.
+pub struct Task<const LEVEL: u8> {
+    /// Interrupt threshold raised by the locks currently held, `LEVEL` if none
+    threshold: Cell<u8>,
+    _not_send: PhantomData<*const ()>,
+}
+
+impl<const LEVEL: u8> Task<LEVEL> {
+    /// # Safety
+    ///
+    /// * Must be called from code running at interrupt level `LEVEL`, i.e., an
+    ///   interrupt handler configured with `LEVEL`, or thread mode if `LEVEL`
+    ///   is 0.
+    /// * Must not be called more than once in the same invocation of the task.
+    #[inline]
+    pub unsafe fn new() -> Self {
+        Self {
+            threshold: Cell::new(LEVEL),
+            _not_send: PhantomData,
+        }
+    }
+}
+
+/// Runs `f` with interrupts at or below `ceiling` held off
+///
+/// Raises the interrupt threshold to `ceiling`, or disables interrupts globally
+/// for a ceiling of [MAX_LEVEL].
+#[inline(always)]
+pub(crate) fn with_ceiling<R>(ceiling: u8, f: impl FnOnce() -> R) -> R {
+    if ceiling == MAX_LEVEL {
+        riscv::interrupt::free(f)
+    } else {
+        with_threshold(ceiling, f)
+    }
+}
+
+/// Data shared by tasks at or below interrupt level `CEILING`
+///
+/// Exclusive access relies on the threshold staying at or above `CEILING` for
+/// the duration of a lock, which is why writing `mintthresh` directly is
+/// `unsafe`, sa. [mintthresh::write](crate::register::mintthresh::write).
+pub struct Resource<T, const CEILING: u8> {
+    data: UnsafeCell<T>,
+    /// Set while the data is borrowed. Only accessed at the ceiling, so
+    /// load/store suffices.
+    locked: AtomicBool,
+}
+
+// SAFETY: access is serialized by `lock`. This relies on `mintthresh` not being
+// lowered while a lock is held, which only `unsafe` code can do, sa.
+// `register::mintthresh::write`.
+unsafe impl<T: Send, const CEILING: u8> Sync for Resource<T, CEILING> {}
+
+/// Evaluates to an error at compile time if `LEVEL` exceeds `CEILING`
+struct CeilingCheck<const LEVEL: u8, const CEILING: u8>;
+
+impl<const LEVEL: u8, const CEILING: u8> CeilingCheck<LEVEL, CEILING> {
+    const OK: () = assert!(
+        LEVEL <= CEILING,
+        "task level exceeds the ceiling of the resource"
+    );
+}
+
+impl<T, const CEILING: u8> Resource<T, CEILING> {
+    #[inline]
+    pub const fn new(data: T) -> Self {
+        Self {
+            data: UnsafeCell::new(data),
+            locked: AtomicBool::new(false),
+        }
+    }
+
+    /// Runs `f` with exclusive access to the data
+    ///
+    /// Raises the interrupt threshold to `CEILING` for the duration of `f`. No
+    /// lock is needed when `task` is already at or above the ceiling, e.g.,
+    /// when `task` is at the ceiling or `f` is nested in the lock of another
+    /// resource with a higher ceiling.
+    ///
+    /// Fails to compile if `LEVEL` exceeds `CEILING`.
+    ///
+    /// # Panics
+    ///
+    /// * If called from within `f`, i.e., when the resource is locked already.
+    #[inline]
+    pub fn lock<const LEVEL: u8, R>(&self, task: &Task<LEVEL>, f: impl FnOnce(&mut T) -> R) -> R {
+        #[allow(clippy::let_unit_value)]
+        let () = CeilingCheck::<LEVEL, CEILING>::OK;
+
+        let prev = task.threshold.get();
+        if prev >= CEILING {
+            self.access(f)
+        } else {
+            task.threshold.set(CEILING);
+            let r = with_ceiling(CEILING, || self.access(f));
+            task.threshold.set(prev);
+            r
+        }
+    }
+
+    /// Runs `f` on the data, must be called at the ceiling
+    #[inline]
+    fn access<R>(&self, f: impl FnOnce(&mut T) -> R) -> R {
+        assert!(
+            !self.locked.load(Ordering::Relaxed),
+            "resource is locked already"
+        );
+        self.locked.store(true, Ordering::Relaxed);
+        // SAFETY: tasks sharing the resource are at or below CEILING, and the
+        // flag rules out nested borrows from the lock holder
+        let r = f(unsafe { &mut *self.data.get() });
+        self.locked.store(false, Ordering::Relaxed);
+        r
+    }
+}
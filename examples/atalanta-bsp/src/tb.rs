@@ -115,14 +115,16 @@ pub fn rtl_tb_signal_ok() {
 #[cfg(all(feature = "fpga", feature = "rt"))]
 fn blink_exception(_trap_frame: &riscv_rt::TrapFrame) -> ! {
     use crate::{
-        asm_delay,
+        cycles::CycleDelay,
         led::{led_off, led_set, Led},
-        sprintln, NOPS_PER_SEC,
+        sprintln,
     };
+    use embedded_hal::delay::DelayNs;
 
     // Initialize UART if not initialized
-    let mut uart = if !unsafe { crate::uart::UART_IS_INIT } {
-        ApbUart::init(crate::CPU_FREQ, DEFAULT_BAUD)
+    let mut uart = if !crate::uart::UART_IS_INIT.load(core::sync::atomic::Ordering::Relaxed) {
+        // SAFETY: no driver owns the UART, since it has not been initialized
+        unsafe { ApbUart::init(crate::CPU_FREQ, DEFAULT_BAUD) }
     } else {
         // Safety: UART is initialized, and no one is going to use it after this
         // exception
@@ -135,38 +137,50 @@ fn blink_exception(_trap_frame: &riscv_rt::TrapFrame) -> ! {
     let code = riscv::register::mcause::read().code();
     sprintln!("\r\nException: {}", code);
 
+    // Don't start a timer from the exception handler, mcycle needs no setup
+    let mut delay = CycleDelay;
+    // SAFETY: the program has ended, the leds are taken over from any owned pins
     loop {
         // Show 4 LSBs of the exception code on the leds (use GDB to read the rest)
-        led_set(Led::Ld0, code & 0b1 == 0b1);
-        led_set(Led::Ld1, code >> 1 & 0b1 == 0b1);
-        led_set(Led::Ld2, code >> 2 & 0b1 == 0b1);
-        led_set(Led::Ld3, code >> 3 & 0b1 == 0b1);
+        unsafe {
+            led_set(Led::Ld0, code & 0b1 == 0b1);
+            led_set(Led::Ld1, code >> 1 & 0b1 == 0b1);
+            led_set(Led::Ld2, code >> 2 & 0b1 == 0b1);
+            led_set(Led::Ld3, code >> 3 & 0b1 == 0b1);
+        }
 
-        asm_delay(NOPS_PER_SEC * 4 / 5);
+        delay.delay_ms(800);
 
-        led_off(Led::Ld0);
-        led_off(Led::Ld1);
-        led_off(Led::Ld2);
-        led_off(Led::Ld3);
+        unsafe {
+            led_off(Led::Ld0);
+            led_off(Led::Ld1);
+            led_off(Led::Ld2);
+            led_off(Led::Ld3);
+        }
 
-        asm_delay(NOPS_PER_SEC / 5);
+        delay.delay_ms(200);
     }
 }
 
 #[cfg(all(feature = "fpga", feature = "panic"))]
 pub(crate) fn blink_panic() -> ! {
     use crate::{
-        asm_delay,
+        cycles::CycleDelay,
         led::{led_off, led_on, Led},
-        NOPS_PER_SEC,
     };
+    use embedded_hal::delay::DelayNs;
 
+    let mut delay = CycleDelay;
     let ord = [Led::Ld3, Led::Ld1, Led::Ld2, Led::Ld0, Led::Ld3].windows(2);
-    let delay = NOPS_PER_SEC / ord.len() as u32;
+    let delay_ms = 1000 / ord.len() as u32;
     for leds in ord.cycle() {
-        led_off(leds[0]);
-        led_on(leds[1]);
-        asm_delay(delay);
+        // SAFETY: the program has ended, the leds are taken over from any owned
+        // pins
+        unsafe {
+            led_off(leds[0]);
+            led_on(leds[1]);
+        }
+        delay.delay_ms(delay_ms);
     }
 
     unreachable!()
@@ -176,20 +190,28 @@ pub(crate) fn blink_panic() -> ! {
 #[cfg(feature = "fpga")]
 pub fn wait_blink() -> ! {
     use crate::{
-        asm_delay,
+        cycles::CycleDelay,
         led::{led_off, led_on, Led},
-        NOPS_PER_SEC,
     };
+    use embedded_hal::delay::DelayNs;
 
-    led_off(Led::Ld0);
-    led_off(Led::Ld1);
+    // SAFETY: the program has ended, the leds are taken over from any owned pins
+    unsafe {
+        led_off(Led::Ld0);
+        led_off(Led::Ld1);
+    }
 
+    let mut delay = CycleDelay;
     let ord = [Led::Ld3, Led::Ld2, Led::Ld3].windows(2);
-    let delay = NOPS_PER_SEC / ord.len() as u32;
+    let delay_ms = 1000 / ord.len() as u32;
     for leds in ord.cycle() {
-        led_off(leds[0]);
-        led_on(leds[1]);
-        asm_delay(delay);
+        // SAFETY: the program has ended, the leds are taken over from any owned
+        // pins
+        unsafe {
+            led_off(leds[0]);
+            led_on(leds[1]);
+        }
+        delay.delay_ms(delay_ms);
     }
 
     unreachable!()
@@ -199,21 +221,29 @@ pub fn wait_blink() -> ! {
 #[cfg(feature = "fpga")]
 fn ok_blink() -> ! {
     use crate::{
-        asm_delay,
+        cycles::CycleDelay,
         led::{led_off, led_on, Led},
-        NOPS_PER_SEC,
     };
+    use embedded_hal::delay::DelayNs;
 
-    led_off(Led::Ld2);
-    led_off(Led::Ld3);
+    // SAFETY: the program has ended, the leds are taken over from any owned pins
+    unsafe {
+        led_off(Led::Ld2);
+        led_off(Led::Ld3);
+    }
 
+    let mut delay = CycleDelay;
     loop {
-        led_on(Led::Ld0);
-        led_on(Led::Ld1);
-        asm_delay(NOPS_PER_SEC / 4);
-        led_off(Led::Ld0);
-        led_off(Led::Ld1);
-        asm_delay(NOPS_PER_SEC / 4);
+        unsafe {
+            led_on(Led::Ld0);
+            led_on(Led::Ld1);
+        }
+        delay.delay_ms(250);
+        unsafe {
+            led_off(Led::Ld0);
+            led_off(Led::Ld1);
+        }
+        delay.delay_ms(250);
     }
 }
 
@@ -227,18 +257,27 @@ fn fail_blink() -> ! {
         () => rtl_tb_signal_fail(),
         #[cfg(not(feature = "rtl-tb"))]
         () => {
-            use crate::{asm_delay, NOPS_PER_SEC};
+            use crate::cycles::CycleDelay;
+            use embedded_hal::delay::DelayNs;
+
+            let mut delay = CycleDelay;
+            // SAFETY: the program has ended, the leds are taken over from any
+            // owned pins
             loop {
-                led_on(Led::Ld0);
-                led_on(Led::Ld1);
-                led_on(Led::Ld2);
-                led_on(Led::Ld3);
-                asm_delay(NOPS_PER_SEC);
-                led_off(Led::Ld0);
-                led_off(Led::Ld1);
-                led_off(Led::Ld2);
-                led_off(Led::Ld3);
-                asm_delay(NOPS_PER_SEC);
+                unsafe {
+                    led_on(Led::Ld0);
+                    led_on(Led::Ld1);
+                    led_on(Led::Ld2);
+                    led_on(Led::Ld3);
+                }
+                delay.delay_ms(1000);
+                unsafe {
+                    led_off(Led::Ld0);
+                    led_off(Led::Ld1);
+                    led_off(Led::Ld2);
+                    led_off(Led::Ld3);
+                }
+                delay.delay_ms(1000);
             }
         }
     }
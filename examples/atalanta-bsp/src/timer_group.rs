@@ -1,7 +1,12 @@
+use embedded_hal::delay::DelayNs;
+use riscv::asm::wfi;
+
 use crate::{
+    clic::{Clic, Polarity, Trig},
     mask_u32p,
     mmap::{apb_timer::*, CFG_BASE, PERIPH_CLK_DIV_OFS},
-    read_u32p, read_u8_masked, unmask_u32p, write_u32p, CPU_FREQ,
+    peripherals::{TIMER0, TIMER1, TIMER2, TIMER3},
+    read_u32p, read_u8_masked, unmask_u32p, write_u32p, Interrupt, CPU_FREQ,
 };
 
 /// Relocatable driver for PULP APB Timer IP
@@ -9,8 +14,14 @@ pub struct Timer(*mut RegisterBlock);
 
 impl Timer {
     /// Initializes a timer with all values initialized to zero
+    ///
+    /// # Safety
+    ///
+    /// * Bypasses the ownership tokens of [Peripherals](crate::Peripherals). No
+    ///   other driver for the timer at `BASE_ADDR` may be in use. Prefer
+    ///   `Timer::from(p.timer0)` and alike.
     #[inline]
-    pub fn init<const BASE_ADDR: usize>() -> Self {
+    pub unsafe fn init<const BASE_ADDR: usize>() -> Self {
         let timer = Self(BASE_ADDR as *mut _);
         // Disable timer & zero prescaler
         write_u32p(unsafe { &mut (*timer.0).ctrl as *mut u32 }, 0);
@@ -38,10 +49,10 @@ impl Timer {
 
     /// Starts the count
     ///
-    /// `prescaler` must be less than 7
+    /// `prescaler` must be less than or equal to [MAX_PRESCALER]
     #[inline]
     pub fn enable_with_prescaler(&mut self, prescaler: u32) {
-        debug_assert!(prescaler <= 0b111);
+        debug_assert!(prescaler <= MAX_PRESCALER);
 
         write_u32p(
             unsafe { &mut (*self.0).ctrl as *mut u32 },
@@ -49,6 +60,46 @@ impl Timer {
         );
     }
 
+    /// Returns the prescaler. The counter advances once every `prescaler + 1`
+    /// peripheral clock cycles.
+    #[inline]
+    pub fn prescaler(&self) -> u32 {
+        let ctrl = read_u32p(unsafe { &mut (*self.0).ctrl as *mut u32 });
+        (ctrl >> TIMER_CTRL_PRESCALER_BIT_IDX) & MAX_PRESCALER
+    }
+
+    /// Sets the prescaler without changing whether the timer is counting
+    ///
+    /// `prescaler` must be less than or equal to [MAX_PRESCALER]
+    #[inline]
+    pub fn set_prescaler(&mut self, prescaler: u32) {
+        debug_assert!(prescaler <= MAX_PRESCALER);
+
+        let ctrl = unsafe { &mut (*self.0).ctrl as *mut u32 };
+        write_u32p(
+            ctrl,
+            (read_u32p(ctrl) & !(MAX_PRESCALER << TIMER_CTRL_PRESCALER_BIT_IDX))
+                | (prescaler << TIMER_CTRL_PRESCALER_BIT_IDX),
+        );
+    }
+
+    /// Returns the effective tick rate in Hz, i.e., [CPU_FREQ] divided by the
+    /// peripheral clock divider and the prescaler
+    #[inline]
+    pub fn tick_hz(&self) -> u32 {
+        CPU_FREQ / cycles_per_tick(self.prescaler()) as u32
+    }
+
+    /// Converts `duration` to ticks at the current prescaler
+    ///
+    /// # Errors
+    ///
+    /// Returns [PeriodOverflow] if `duration` exceeds the 32-bit counter.
+    #[inline]
+    pub fn duration_to_ticks(&self, duration: Duration) -> Result<u32, PeriodOverflow> {
+        to_ticks(duration, self.prescaler())
+    }
+
     /// Stops the count
     #[inline]
     pub fn disable(&mut self) {
@@ -89,14 +140,100 @@ impl Timer {
         write_u32p(unsafe { &mut (*self.0).cmp as *mut u32 }, cmp);
     }
 
+    /// Returns the compare interrupt of this timer
+    #[inline]
+    pub fn interrupt(&self) -> Interrupt {
+        match self.0 as usize {
+            TIMER0_ADDR => Interrupt::Timer0Cmp,
+            TIMER1_ADDR => Interrupt::Timer1Cmp,
+            TIMER2_ADDR => Interrupt::Timer2Cmp,
+            TIMER3_ADDR => Interrupt::Timer3Cmp,
+            _ => unreachable!(),
+        }
+    }
+
     #[inline]
     pub fn into_periodic(self) -> Periodic {
         Periodic(self)
     }
+
+    /// Configures the compare interrupt as positive edge-triggered, so that
+    /// the wake-up is latched, sa. [SleepingDelay]
+    #[inline]
+    pub fn into_sleeping_delay(self) -> SleepingDelay {
+        let int = self.interrupt();
+        Clic::attr(int).set_trig(Trig::Edge);
+        Clic::attr(int).set_polarity(Polarity::Pos);
+        SleepingDelay(self)
+    }
+
+    /// Stops the timer and zeros the counter at prescaler 0 & compare `cmp`
+    #[inline]
+    fn reset_for_delay(&mut self, cmp: u32) {
+        self.disable();
+        self.set_prescaler(0);
+        // Setting CMP also zeros COUNTER, so the count starts at a tick boundary
+        self.set_cmp(cmp);
+    }
+}
+
+macro_rules! impl_from_token {
+    ($($token:ident),*) => {
+        $(
+            impl From<$token> for Timer {
+                /// Initializes the timer owned by the token, sa. [Timer::init]
+                #[inline]
+                fn from(_timer: $token) -> Self {
+                    // SAFETY: the token proves ownership of the timer
+                    unsafe { Self::init::<{ $token::BASE }>() }
+                }
+            }
+        )*
+    };
 }
 
+impl_from_token!(TIMER0, TIMER1, TIMER2, TIMER3);
+
+/// Largest value of the 3-bit prescaler
+pub const MAX_PRESCALER: u32 = 0b111;
+
 const DENOM: u32 = CPU_FREQ;
-pub type Duration = fugit::Duration<u32, 1, DENOM>;
+pub type Duration = fugit::Duration<u64, 1, DENOM>;
+
+/// Duration exceeds the 32-bit counter, even at the largest prescaler if
+/// selected automatically
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "ufmt", derive(crate::ufmt::derive::uDebug))]
+pub struct PeriodOverflow;
+
+/// Returns the number of CPU clock cycles per tick at `prescaler`
+#[inline]
+fn cycles_per_tick(prescaler: u32) -> u64 {
+    // Read current peripheral clock divider to dynamically fixup fugit::Duration.
+    // A divider of zero is treated as one to keep the result non-zero.
+    let pclk_div = unsafe { read_u8_masked(CFG_BASE + PERIPH_CLK_DIV_OFS, 0xf) }.max(1);
+    pclk_div as u64 * (prescaler as u64 + 1)
+}
+
+#[inline]
+fn to_ticks(duration: Duration, prescaler: u32) -> Result<u32, PeriodOverflow> {
+    u32::try_from(duration.ticks() / cycles_per_tick(prescaler)).map_err(|_| PeriodOverflow)
+}
+
+/// Returns the number of ticks at prescaler 0 in which at least `ns` pass
+#[inline]
+fn delay_ticks(ns: u32) -> u32 {
+    // Fits, since `u32::MAX` ns is less than `u32::MAX` CPU clock cycles
+    (ns as u64 * CPU_FREQ as u64).div_ceil(1_000_000_000 * cycles_per_tick(0)) as u32
+}
+
+/// Returns the smallest prescaler at which `duration` fits the counter
+#[inline]
+fn fitting_prescaler(duration: Duration) -> Result<u32, PeriodOverflow> {
+    (0..=MAX_PRESCALER)
+        .find(|&prescaler| to_ticks(duration, prescaler).is_ok())
+        .ok_or(PeriodOverflow)
+}
 
 pub struct Periodic(Timer);
 
@@ -104,28 +241,52 @@ impl Periodic {
     /// Schedules an interrupt to be fired every `duration`. Call [Self::start]
     /// to start the timer.
     ///
-    /// Also resets the internal counter.
+    /// Selects the smallest prescaler at which `duration` fits the counter,
+    /// maximizing resolution. Also resets the internal counter.
+    ///
+    /// # Errors
+    ///
+    /// Returns [PeriodOverflow] if `duration` exceeds the counter even at
+    /// [MAX_PRESCALER]. The timer is left unchanged.
     #[inline]
-    pub fn set_period(&mut self, duration: Duration) {
-        // Read current peripheral clock divider to dynamically fixup fugit::Duration
-        let pclk_div = unsafe { read_u8_masked(CFG_BASE + PERIPH_CLK_DIV_OFS, 0xf) };
+    pub fn set_period(&mut self, duration: Duration) -> Result<(), PeriodOverflow> {
+        let prescaler = fitting_prescaler(duration)?;
+        let ticks = to_ticks(duration, prescaler)?;
 
+        self.0.set_prescaler(prescaler);
         // Setting CMP also sets COUNTER
-        self.0.set_cmp(duration.ticks() / pclk_div as u32);
+        self.0.set_cmp(ticks);
+        Ok(())
     }
 
     /// Schedules an interrupt to be fired every `duration`
     ///
     /// Also sets the counter to a specific value, allowing to trigger the first
-    /// interrupt ahead of schedule.
+    /// interrupt ahead of schedule. The prescaler is selected as in
+    /// [Self::set_period].
+    ///
+    /// # Errors
+    ///
+    /// Returns [PeriodOverflow] if `period` exceeds the counter even at
+    /// [MAX_PRESCALER]. The timer is left unchanged.
     #[inline]
-    pub fn set_period_offset(&mut self, period: Duration, offset: Duration) {
-        // Read current peripheral clock divider to dynamically fixup fugit::Duration
-        let pclk_div = unsafe { read_u8_masked(CFG_BASE + PERIPH_CLK_DIV_OFS, 0xf) };
+    pub fn set_period_offset(
+        &mut self,
+        period: Duration,
+        offset: Duration,
+    ) -> Result<(), PeriodOverflow> {
+        let prescaler = fitting_prescaler(period)?;
+        let period_ticks = to_ticks(period, prescaler)?;
+        // Counter must not exceed compare, sa. [Timer::set_counter]
+        let offset_ticks = to_ticks(offset, prescaler)
+            .unwrap_or(u32::MAX)
+            .min(period_ticks);
 
+        self.0.set_prescaler(prescaler);
         // Setting CMP also sets COUNTER, so we override that afterwards
-        self.0.set_cmp(period.ticks() / pclk_div as u32);
-        self.0.set_counter(offset.ticks() / pclk_div as u32);
+        self.0.set_cmp(period_ticks);
+        self.0.set_counter(offset_ticks);
+        Ok(())
     }
 
     /// Starts the timer
@@ -141,3 +302,73 @@ impl Periodic {
         self.0.set_cmp(u32::MAX);
     }
 }
+
+/// Busy-waits on the counter
+///
+/// Reconfigures the timer: prescaler & compare are overwritten and the timer
+/// is left disabled.
+impl DelayNs for Timer {
+    #[inline]
+    fn delay_ns(&mut self, ns: u32) {
+        if ns == 0 {
+            return;
+        }
+        let ticks = delay_ticks(ns);
+        self.reset_for_delay(u32::MAX);
+        self.enable();
+        while self.counter() < ticks {}
+        self.disable();
+    }
+}
+
+/// Delay that sleeps on `wfi` until the compare interrupt of the timer
+///
+/// Interrupts are masked while sleeping so that the compare interrupt wakes
+/// the core without trapping. Other interrupts are serviced after the delay.
+/// The timer is left disabled with compare at `u32::MAX`.
+pub struct SleepingDelay(Timer);
+
+impl DelayNs for SleepingDelay {
+    #[inline]
+    fn delay_ns(&mut self, ns: u32) {
+        if ns == 0 {
+            return;
+        }
+        let timer = &mut self.0;
+        let int = timer.interrupt();
+        let ie = Clic::ie(int);
+        let ip = Clic::ip(int);
+
+        riscv::interrupt::free(|| {
+            timer.reset_for_delay(delay_ticks(ns));
+            let was_enabled = ie.is_enabled();
+            // SAFETY: compare interrupt is edge-triggered, software writes are
+            // supported
+            unsafe { ip.unpend() };
+            // SAFETY: interrupts are masked globally
+            unsafe { ie.enable() };
+            timer.enable();
+
+            // Wake-ups by other interrupts are spurious
+            // SAFETY: compare interrupt is edge-triggered
+            while !unsafe { ip.is_pending() } {
+                wfi();
+            }
+
+            timer.disable();
+            timer.set_cmp(u32::MAX);
+            // SAFETY: compare interrupt is edge-triggered, software writes are
+            // supported
+            unsafe { ip.unpend() };
+            if !was_enabled {
+                ie.disable();
+            }
+        });
+    }
+}
+
+impl From<SleepingDelay> for Timer {
+    fn from(value: SleepingDelay) -> Self {
+        value.0
+    }
+}
@@ -15,6 +15,15 @@ fn setup_interrupt_vector() {
         mtvec::write(bits, mtvec::TrapMode::Clic);
         mtvt::write(bits, mtvt::TrapMode::Clic);
 
+        // Route exceptions & non-SHV interrupts to the software vectored trap
+        #[cfg(feature = "mnxti")]
+        {
+            extern "C" {
+                fn _start_mnxti_trap();
+            }
+            mtvec::write(_start_mnxti_trap as usize, mtvec::TrapMode::Clic);
+        }
+
         mintthresh::write(0x00.into());
     }
 }
@@ -22,6 +31,8 @@ fn setup_interrupt_vector() {
 // The vector table
 //
 // N.b. vectors length must be exactly 0x80
+//
+// The interrupt entries are generated by build.rs from `interrupt_table.rs`
 #[cfg(feature = "rt")]
 global_asm!(
     "
@@ -38,64 +49,21 @@ global_asm!(
 
     _vector_table:
         // Use [0] as exception entry point
-        j _start_trap
-        // [1..=16] are standard
-        .word _start_SupervisorSoft_trap    // 1
-        .word _start_DefaultHandler_trap
-        .word _start_MachineSoft_trap       // 3
-        .word _start_DefaultHandler_trap
-        .word _start_SupervisorTimer_trap   // 5
-        .word _start_DefaultHandler_trap
-        .word _start_MachineTimer_trap      // 7
-        .word _start_DefaultHandler_trap
-        .word _start_SupervisorExternal_trap // 9
-        .word _start_DefaultHandler_trap
-        .word _start_MachineExternal_trap   // 11
-        .rept 5
-        .word _start_DefaultHandler_trap    // 12..=16
-        .endr
-        .word _start_Uart_trap              // 17
-        .word _start_Gpio_trap              // 18
-        .word _start_SpiRxTxIrq_trap        // 19
-        .word _start_SpiEotIrq_trap         // 20
-        .word _start_Timer0Ovf_trap         // 21
-        .word _start_Timer0Cmp_trap         // 22
-        .word _start_Timer1Ovf_trap         // 23
-        .word _start_Timer1Cmp_trap         // 24
-        .word _start_Timer2Ovf_trap         // 25
-        .word _start_Timer2Cmp_trap         // 26
-        .word _start_Timer3Ovf_trap         // 27
-        .word _start_Timer3Cmp_trap         // 28
-
-        // Pad with `DefaultHandler`
-        .rept 2
-        .word _start_DefaultHandler_trap    // 29..=30
-        .endr
-
-        .word _start_Nmi_trap   // 31
-        .word _start_Dma0_trap  // 32
-        .word _start_Dma1_trap  // 33
-        .word _start_Dma2_trap  // 34
-        .word _start_Dma3_trap  // 35
-        .word _start_Dma4_trap  // 36
-        .word _start_Dma5_trap  // 37
-        .word _start_Dma6_trap  // 38
-        .word _start_Dma7_trap  // 39
-        .word _start_Dma8_trap  // 40
-        .word _start_Dma9_trap  // 41
-        .word _start_Dma10_trap // 42
-        .word _start_Dma11_trap // 43
-        .word _start_Dma12_trap // 44
-        .word _start_Dma13_trap // 45
-        .word _start_Dma14_trap // 46
-        .word _start_Dma15_trap // 47
-
-        // Fill the rest with `DefaultHandler`
-        .rept 16
-        .word _start_DefaultHandler_trap // 48..64
-        .endr
-
-        // TODO: add remaining missing interrupts
-
+        j _start_trap",
+    include_str!(concat!(env!("OUT_DIR"), "/vector_table.S")),
+    "
     .option pop",
 );
+
+// Handlers proper of the software vectored `_start_mnxti_trap`
+//
+// The entries are generated by build.rs from `interrupt_table.rs`
+#[cfg(all(feature = "rt", feature = "mnxti"))]
+global_asm!(
+    "
+.section .rodata
+    .global _mnxti_handlers
+    .p2align 2
+    _mnxti_handlers:",
+    include_str!(concat!(env!("OUT_DIR"), "/mnxti_handlers.S")),
+);
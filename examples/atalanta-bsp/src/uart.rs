@@ -1,18 +1,33 @@
 //! Implementation of [PULP APB UART](https://github.com/pulp-platform/apb_uart/) (v0.2.1)
 //!
 //! PULP APB UART conforms to the NS16550.
+//!
+//! [ApbUartHal] is a blocking driver. See [buffered] for an interrupt-driven
+//! alternative.
+pub mod buffered;
+
+use core::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+
+use bitmask_enum::bitmask;
 use embedded_io::Write;
 
-use crate::{mask_u8, mmap::*, read_u8_masked, unmask_u8};
+use crate::{mask_u8, mmap::*, mtimer, peripherals::UART, read_u8_masked, unmask_u8};
 use crate::{read_u8, write_u8};
 
-// Hack to cover some more error cases with outputful panics
-#[cfg(any(all(feature = "fpga", feature = "rt"), feature = "panic"))]
-pub(crate) static mut UART_IS_INIT: bool = false;
+/// Set once the UART has been initialized, so that the output
+/// [sink](crate::sink) and panics know whether they can print
+pub(crate) static UART_IS_INIT: AtomicBool = AtomicBool::new(false);
+
+/// Line errors as [UartError] bits, accumulated over LSR reads
+///
+/// LSR clears its error bits on read. Errors are retained here until reported
+/// by [ApbUartHal::poll_rx].
+static LSR_ERRORS: AtomicU8 = AtomicU8::new(0);
 
 /// When to raise a UART interrupt
-#[derive(Clone)]
-#[repr(u8)]
+///
+/// Flags may be combined with `|`.
+#[bitmask(u8)]
 pub enum UartInterrupt {
     /// Interrupt is raised when...
     ///
@@ -28,6 +43,138 @@ pub enum UartInterrupt {
     OnError = 0b1 << 2,
 }
 
+/// Parity bit configuration
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Parity {
+    None,
+    Odd,
+    Even,
+    /// Parity bit is always set
+    Mark,
+    /// Parity bit is always cleared
+    Space,
+}
+
+/// Stop bit configuration
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum StopBits {
+    One,
+    /// 1.5 stop bits for 5-bit data words, 2 stop bits otherwise
+    Two,
+}
+
+/// Hardware flow control
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum FlowControl {
+    None,
+    /// Transmitter waits for CTS to be asserted
+    Cts,
+    /// [FlowControl::Cts] and RTS is deasserted when the RX FIFO reaches its
+    /// trigger level
+    RtsCts,
+}
+
+/// UART line configuration
+///
+/// Defaults to 8N1 with a 14-byte RX FIFO trigger level and
+/// [FlowControl::Cts].
+///
+/// # Example
+///
+/// ```ignore
+/// let config = UartConfig::new(115_200)
+///     .parity(Parity::Even)
+///     .stop_bits(StopBits::Two);
+/// let serial = ApbUart::new(p.uart, CPU_FREQ, config);
+/// ```
+#[derive(Clone, Copy)]
+pub struct UartConfig {
+    baud: u32,
+    data_bits: UartLcrDataBits,
+    parity: Parity,
+    stop_bits: StopBits,
+    rx_trigger: UartFcrTrigLevel,
+    flow_control: FlowControl,
+}
+
+impl UartConfig {
+    /// * `baud` - target BAUD (sa. UART protocol)
+    #[inline]
+    pub fn new(baud: u32) -> Self {
+        Self {
+            baud,
+            data_bits: UartLcrDataBits::Bits8,
+            parity: Parity::None,
+            stop_bits: StopBits::One,
+            rx_trigger: UartFcrTrigLevel::Bytes14,
+            flow_control: FlowControl::Cts,
+        }
+    }
+
+    #[inline]
+    pub fn baud(mut self, baud: u32) -> Self {
+        self.baud = baud;
+        self
+    }
+
+    #[inline]
+    pub fn data_bits(mut self, data_bits: UartLcrDataBits) -> Self {
+        self.data_bits = data_bits;
+        self
+    }
+
+    #[inline]
+    pub fn parity(mut self, parity: Parity) -> Self {
+        self.parity = parity;
+        self
+    }
+
+    #[inline]
+    pub fn stop_bits(mut self, stop_bits: StopBits) -> Self {
+        self.stop_bits = stop_bits;
+        self
+    }
+
+    /// Sets the RX FIFO fill level at which [UartInterrupt::OnData] is raised
+    #[inline]
+    pub fn rx_trigger(mut self, rx_trigger: UartFcrTrigLevel) -> Self {
+        self.rx_trigger = rx_trigger;
+        self
+    }
+
+    #[inline]
+    pub fn flow_control(mut self, flow_control: FlowControl) -> Self {
+        self.flow_control = flow_control;
+        self
+    }
+
+    /// Value of LCR with `LCR[7]` deasserted
+    fn lcr(&self) -> u8 {
+        let stop = match self.stop_bits {
+            StopBits::One => 0,
+            StopBits::Two => UART_LCR_STOP_BIT,
+        };
+        let parity = match self.parity {
+            Parity::None => 0,
+            Parity::Odd => UART_LCR_PARITY_EN_BIT,
+            Parity::Even => UART_LCR_PARITY_EN_BIT | UART_LCR_PARITY_EVEN_BIT,
+            Parity::Mark => UART_LCR_PARITY_EN_BIT | UART_LCR_PARITY_STICK_BIT,
+            Parity::Space => {
+                UART_LCR_PARITY_EN_BIT | UART_LCR_PARITY_STICK_BIT | UART_LCR_PARITY_EVEN_BIT
+            }
+        };
+        self.data_bits as u8 | stop | parity
+    }
+
+    fn mcr(&self) -> u8 {
+        match self.flow_control {
+            FlowControl::None => 0,
+            FlowControl::Cts => UART_MCR_AFE_BIT,
+            FlowControl::RtsCts => UART_MCR_AFE_BIT | UART_MCR_RTS_BIT,
+        }
+    }
+}
+
 /// Relocatable HAL driver for PULP APB UART
 ///
 /// The type parameter represents the base address for the UART.
@@ -36,20 +183,51 @@ pub struct ApbUartHal<const BASE_ADDR: usize>;
 /// [ApbUartHal]
 pub type ApbUart = ApbUartHal<UART_BASE>;
 
+impl ApbUart {
+    /// Initializes the UART owned by `uart`, sa. [ApbUartHal::init_with_config]
+    #[inline]
+    pub fn new(_uart: UART, freq: u32, config: UartConfig) -> Self {
+        // SAFETY: the token proves ownership of the UART
+        unsafe { Self::init_with_config(freq, config) }
+    }
+}
+
 impl<const BASE_ADDR: usize> ApbUartHal<BASE_ADDR> {
+    /// Initializes the UART with 8N1 and other defaults of [UartConfig]
+    ///
     /// # Parameters
     ///
     /// * `freq` - SoC frequency, used to calculate BAUD rate together with a
     ///   divisor
     /// * `baud` - target BAUD (sa. UART protocol)
+    ///
+    /// # Safety
+    ///
+    /// * Same as [ApbUartHal::init_with_config].
     #[inline]
-    pub fn init(freq: u32, baud: u32) -> Self {
+    pub unsafe fn init(freq: u32, baud: u32) -> Self {
+        Self::init_with_config(freq, UartConfig::new(baud))
+    }
+
+    /// # Parameters
+    ///
+    /// * `freq` - SoC frequency, used to calculate BAUD rate together with a
+    ///   divisor
+    /// * `config` - line configuration
+    ///
+    /// # Safety
+    ///
+    /// * Bypasses the ownership tokens of [Peripherals](crate::Peripherals). No
+    ///   other driver for the UART at `BASE_ADDR` may be in use. Prefer
+    ///   [ApbUart::new].
+    #[inline]
+    pub unsafe fn init_with_config(freq: u32, config: UartConfig) -> Self {
         // Safety: all UART registers are 4-byte aligned which makes the below writes
         // always valid
         unsafe {
             // Read current peripheral clock divider
             let periph_clk_div = read_u8_masked(CFG_BASE + PERIPH_CLK_DIV_OFS, 0xf);
-            let divisor: u32 = freq / periph_clk_div as u32 / (baud << 4);
+            let divisor: u32 = freq / periph_clk_div as u32 / (config.baud << 4);
 
             // Disable all interrupts
             write_u8(BASE_ADDR + UART_IER_DLM_OFS, 0x00);
@@ -60,8 +238,8 @@ impl<const BASE_ADDR: usize> ApbUartHal<BASE_ADDR> {
             write_u8(BASE_ADDR + UART_DLAB_LSB_OFS, divisor as u8);
             // Divisor (hi byte)
             write_u8(BASE_ADDR + UART_DLAB_MSB_OFS, (divisor >> 8) as u8);
-            // 8 bits, no parity, one stop bit
-            write_u8(BASE_ADDR + UART_LCR_OFS, UartLcrDataBits::Bits8 as u8);
+            // Data bits, parity & stop bits
+            write_u8(BASE_ADDR + UART_LCR_OFS, config.lcr());
             // Restore DLAB state
             unmask_u8(BASE_ADDR + UART_LCR_OFS, UART_LCR_DLAB_BIT);
 
@@ -72,18 +250,13 @@ impl<const BASE_ADDR: usize> ApbUartHal<BASE_ADDR> {
                     // Clear RX & TX
                     | UART_FCR_FIFO_RX_RESET_BIT
                     | UART_FCR_FIFO_TX_RESET_BIT
-                    // 14-byte threshold
-                    | UART_FCR_TRIG_RX_LSB
-                    | UART_FCR_TRIG_RX_MSB,
+                    | config.rx_trigger as u8,
             );
-            // Autoflow mode
-            write_u8(BASE_ADDR + UART_MCR_OFS, 0x20);
+            // Flow control
+            write_u8(BASE_ADDR + UART_MCR_OFS, config.mcr());
         }
 
-        #[cfg(any(all(feature = "fpga", feature = "rt"), feature = "panic"))]
-        unsafe {
-            UART_IS_INIT = true
-        };
+        UART_IS_INIT.store(true, Ordering::Relaxed);
 
         Self {}
     }
@@ -99,7 +272,7 @@ impl<const BASE_ADDR: usize> ApbUartHal<BASE_ADDR> {
 
     #[inline]
     pub fn write_str(&mut self, s: &str) {
-        // SAFETY: UART impl is currently infallible
+        // SAFETY: UART writes are infallible
         unsafe { self.write(s.as_bytes()).unwrap_unchecked() };
     }
 
@@ -113,49 +286,251 @@ impl<const BASE_ADDR: usize> ApbUartHal<BASE_ADDR> {
         unsafe { write_u8(BASE_ADDR + UART_RBR_THR_DLL_OFS, c) };
     }
 
+    /// Reads a byte from UART, blocking until one is available
+    ///
+    /// Line errors are ignored. Use [ApbUartHal::read_byte] to detect them.
     #[inline]
     pub fn getc(&mut self) -> u8 {
         // Wait for data to become ready
-        while unsafe { read_u8(BASE_ADDR + UART_LSR_OFS) } & UART_LSR_RX_FIFO_VALID_BIT == 0 {}
+        while self.read_lsr() & UART_LSR_RX_FIFO_VALID_BIT == 0 {}
 
         // SAFETY: UART0_ADDR is 4-byte aligned
         unsafe { read_u8(BASE_ADDR) }
     }
 
-    /// Assert bit to cause hardware to raise an interrupt on specified UART
-    /// interrupt.
+    /// Assert bits to cause hardware to raise an interrupt on specified UART
+    /// interrupts. Interrupts enabled previously remain enabled.
     #[inline]
     pub fn listen(&mut self, int: UartInterrupt) {
+        self.modify_ier(|ier| ier | int.bits);
+    }
+
+    /// Deassert bits to stop hardware from raising an interrupt on specified
+    /// UART interrupts
+    #[inline]
+    pub fn unlisten(&mut self, int: UartInterrupt) {
+        self.modify_ier(|ier| ier & !int.bits);
+    }
+
+    /// Returns the currently enabled UART interrupts
+    #[inline]
+    pub fn listening(&self) -> UartInterrupt {
+        let ier = self.with_ier(|| unsafe { read_u8(BASE_ADDR + UART_IER_DLM_OFS) });
+        UartInterrupt {
+            // IER[7:3] are reserved
+            bits: ier & 0b111,
+        }
+    }
+
+    /// Read-modify-write IER with `f`
+    #[inline]
+    fn modify_ier(&self, f: impl FnOnce(u8) -> u8) {
+        self.with_ier(|| unsafe {
+            let ier = read_u8(BASE_ADDR + UART_IER_DLM_OFS);
+            write_u8(BASE_ADDR + UART_IER_DLM_OFS, f(ier));
+        })
+    }
+
+    /// Runs `f` with `LCR[7]` deasserted, i.e., with IER accessible at
+    /// [UART_IER_DLM_OFS]
+    #[inline]
+    fn with_ier<R>(&self, f: impl FnOnce() -> R) -> R {
         unsafe {
             // Save LCR for restoration
             let p_lcr = read_u8(BASE_ADDR + UART_LCR_OFS);
 
             // Deassert `LCR[7]` => IER_DLM is IER
-            let lcr = p_lcr & (0b1 << 7);
-            write_u8(BASE_ADDR + UART_LCR_OFS, lcr);
+            write_u8(BASE_ADDR + UART_LCR_OFS, p_lcr & !UART_LCR_DLAB_BIT);
 
-            // Set IER
-            write_u8(BASE_ADDR + UART_IER_DLM_OFS, int as u8);
+            let r = f();
 
             // Restore `LCR`
             write_u8(BASE_ADDR + UART_LCR_OFS, p_lcr);
+            r
+        }
+    }
+
+    /// Returns `true` if there is at least one byte in the receive FIFO
+    ///
+    /// Line errors are retained for [ApbUartHal::poll_rx].
+    #[inline]
+    pub fn is_data_ready(&self) -> bool {
+        self.read_lsr() & UART_LSR_RX_FIFO_VALID_BIT != 0
+    }
+
+    /// Reads a byte from UART, blocking until one is available or a line error
+    /// is detected
+    #[inline]
+    pub fn read_byte(&mut self) -> Result<u8, UartError> {
+        while !self.poll_rx()? {}
+        // Safety: UART_RBR is 4-byte aligned
+        Ok(unsafe { read_u8(BASE_ADDR + UART_RBR_THR_DLL_OFS) })
+    }
+
+    /// Returns `Ok(true)` if there is at least one byte in the receive FIFO or
+    /// the line error reported by LSR
+    ///
+    /// On a parity, framing or break error the offending character is
+    /// discarded from the receive FIFO. On an overrun error the FIFO holds
+    /// valid characters received before the overrun.
+    #[inline]
+    pub fn poll_rx(&mut self) -> Result<bool, UartError> {
+        let lsr = self.read_lsr();
+        if let Some(err) = take_lsr_errors() {
+            if err != UartError::Overrun && lsr & UART_LSR_RX_FIFO_VALID_BIT != 0 {
+                // Safety: UART_RBR is 4-byte aligned
+                unsafe { read_u8(BASE_ADDR + UART_RBR_THR_DLL_OFS) };
+            }
+            return Err(err);
+        }
+        Ok(lsr & UART_LSR_RX_FIFO_VALID_BIT != 0)
+    }
+
+    /// Reads available bytes from UART, blocking until at least one is
+    /// received or `timeout` has elapsed on [mtimer::Monotonic]
+    ///
+    /// Returns `Ok(0)` on timeout. mtimer must be enabled.
+    #[inline]
+    pub fn read_with_deadline(
+        &mut self,
+        buf: &mut [u8],
+        timeout: mtimer::Duration,
+    ) -> Result<usize, UartError> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        let deadline = mtimer::Monotonic::now() + timeout;
+
+        loop {
+            if self.poll_rx()? {
+                let n = self.read_burst(buf);
+                // An empty burst means a line error, reported by the next poll
+                if n != 0 {
+                    return Ok(n);
+                }
+            } else if mtimer::Monotonic::now() >= deadline {
+                return Ok(0);
+            }
         }
     }
 
+    /// Moves bytes from the RX FIFO into `buf` until either is exhausted
+    ///
+    /// A line error ends the burst and is retained for the next read.
+    #[inline]
+    fn read_burst(&mut self, buf: &mut [u8]) -> usize {
+        let mut n = 0;
+        while n < buf.len() {
+            // Safety: UART_LINE_STATUS is 4-byte aligned
+            let lsr = unsafe { read_u8(BASE_ADDR + UART_LSR_OFS) };
+            if lsr & UART_LSR_ERROR_MASK != 0 {
+                accumulate_lsr_errors(lsr);
+                break;
+            }
+            if lsr & UART_LSR_RX_FIFO_VALID_BIT == 0 {
+                break;
+            }
+            // Safety: UART_RBR is 4-byte aligned
+            buf[n] = unsafe { read_u8(BASE_ADDR + UART_RBR_THR_DLL_OFS) };
+            n += 1;
+        }
+        n
+    }
+
     #[inline]
     fn is_transmit_empty(&self) -> bool {
+        self.read_lsr() & UART_LSR_TX_FIFO_EMPTY_BIT != 0
+    }
+
+    /// Reads LSR, retaining line errors in [LSR_ERRORS]
+    #[inline]
+    fn read_lsr(&self) -> u8 {
         // Safety: UART_LINE_STATUS is 4-byte aligned
-        unsafe { (read_u8(BASE_ADDR + UART_LSR_OFS) & 0x20) != 0 }
+        let lsr = unsafe { read_u8(BASE_ADDR + UART_LSR_OFS) };
+        if lsr & UART_LSR_ERROR_MASK != 0 {
+            accumulate_lsr_errors(lsr);
+        }
+        lsr
+    }
+}
+
+#[inline]
+fn accumulate_lsr_errors(lsr: u8) {
+    riscv::interrupt::free(|| {
+        let errors = LSR_ERRORS.load(Ordering::Relaxed);
+        LSR_ERRORS.store(errors | (lsr & UART_LSR_ERROR_MASK), Ordering::Relaxed);
+    })
+}
+
+/// Takes the most significant line error retained in [LSR_ERRORS]
+#[inline]
+fn take_lsr_errors() -> Option<UartError> {
+    // Fast path, no errors
+    if LSR_ERRORS.load(Ordering::Relaxed) == 0 {
+        return None;
     }
+    riscv::interrupt::free(|| {
+        let errors = LSR_ERRORS.load(Ordering::Relaxed);
+        LSR_ERRORS.store(0, Ordering::Relaxed);
+        UartError::from_lsr(errors)
+    })
 }
 
-#[derive(Debug)]
-pub struct UartError;
+/// UART line error as reported by LSR
+///
+/// The discriminants match the error bits of [UART_LSR_OFS].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "ufmt", derive(crate::ufmt::derive::uDebug))]
+#[repr(u8)]
+pub enum UartError {
+    /// A character was lost due to a full RX FIFO
+    ///
+    /// Maps to [embedded_io::ErrorKind::OutOfMemory].
+    Overrun = UART_LSR_OVERRUN_BIT,
+    /// A character was received with an incorrect parity bit
+    ///
+    /// Maps to [embedded_io::ErrorKind::InvalidData].
+    Parity = UART_LSR_PARITY_BIT,
+    /// A character was received without a valid stop bit, e.g., due to a baud
+    /// rate mismatch
+    ///
+    /// Maps to [embedded_io::ErrorKind::InvalidInput].
+    Framing = UART_LSR_FRAMING_BIT,
+    /// The input was held low for longer than a character
+    ///
+    /// Maps to [embedded_io::ErrorKind::Other].
+    Break = UART_LSR_BREAK_BIT,
+}
+
+impl UartError {
+    /// Decodes the most significant error from the error bits of an LSR value
+    ///
+    /// A break also raises a framing error, so it takes precedence.
+    #[inline]
+    pub fn from_lsr(lsr: u8) -> Option<Self> {
+        if lsr & UART_LSR_BREAK_BIT != 0 {
+            Some(Self::Break)
+        } else if lsr & UART_LSR_FRAMING_BIT != 0 {
+            Some(Self::Framing)
+        } else if lsr & UART_LSR_PARITY_BIT != 0 {
+            Some(Self::Parity)
+        } else if lsr & UART_LSR_OVERRUN_BIT != 0 {
+            Some(Self::Overrun)
+        } else {
+            None
+        }
+    }
+}
 
 impl embedded_io::Error for UartError {
     fn kind(&self) -> embedded_io::ErrorKind {
-        // Error kinds not implemented for BSP right now
-        embedded_io::ErrorKind::Other
+        match self {
+            UartError::Overrun => embedded_io::ErrorKind::OutOfMemory,
+            UartError::Parity => embedded_io::ErrorKind::InvalidData,
+            UartError::Framing => embedded_io::ErrorKind::InvalidInput,
+            UartError::Break => embedded_io::ErrorKind::Other,
+        }
     }
 }
 
@@ -179,3 +554,34 @@ impl<const BASE_ADDR: usize> embedded_io::Write for ApbUartHal<BASE_ADDR> {
         Ok(())
     }
 }
+
+impl<const BASE_ADDR: usize> embedded_io::Read for ApbUartHal<BASE_ADDR> {
+    /// Blocks until at least one byte is received, then drains the RX FIFO
+    /// into `buf`
+    ///
+    /// A line error is returned before any bytes received after it.
+    #[inline]
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        loop {
+            if self.poll_rx()? {
+                let n = self.read_burst(buf);
+                // An empty burst means a line error, reported by the next poll
+                if n != 0 {
+                    return Ok(n);
+                }
+            }
+        }
+    }
+}
+
+impl<const BASE_ADDR: usize> embedded_io::ReadReady for ApbUartHal<BASE_ADDR> {
+    #[inline]
+    fn read_ready(&mut self) -> Result<bool, Self::Error> {
+        // A pending line error is reported by the next read without blocking
+        Ok(self.read_lsr() & UART_LSR_RX_FIFO_VALID_BIT != 0
+            || LSR_ERRORS.load(Ordering::Relaxed) != 0)
+    }
+}
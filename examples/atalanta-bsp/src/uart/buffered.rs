@@ -0,0 +1,280 @@
+//! Interrupt-driven, buffered UART
+//!
+//! Bytes are moved between the hardware FIFOs and static RX & TX ring buffers
+//! by the `Uart` interrupt handler, which must call [on_interrupt]. Reads and
+//! writes only touch the ring buffers, so they don't stall on the hardware.
+//!
+//! Line errors and bytes dropped due to a full RX buffer are recorded by the
+//! interrupt handler and reported by the next read.
+//!
+//! The ring buffers are shared by all instances of [BufferedUart]. Only one
+//! buffered UART may be in use at a time.
+use core::{
+    cell::UnsafeCell,
+    sync::atomic::{AtomicU8, AtomicUsize, Ordering},
+};
+
+use crate::{
+    clic::{Clic, Polarity, Trig},
+    mmap::*,
+    read_u8, write_u8, Interrupt,
+};
+
+use super::{ApbUart, UartError, UartInterrupt};
+
+/// Capacity of the receive buffer in bytes
+pub const RX_BUF_LEN: usize = 128;
+/// Capacity of the transmit buffer in bytes
+pub const TX_BUF_LEN: usize = 128;
+
+static RX: RingBuffer<{ RX_BUF_LEN + 1 }> = RingBuffer::new();
+static TX: RingBuffer<{ TX_BUF_LEN + 1 }> = RingBuffer::new();
+/// Pending line errors as [UartError] bits
+static RX_ERRORS: AtomicU8 = AtomicU8::new(0);
+
+/// Single-producer, single-consumer byte queue
+///
+/// One slot is always kept empty to tell a full buffer from an empty one, so
+/// the capacity is `N - 1`. Only atomic loads & stores are used, which makes
+/// this safe to share between the interrupt handler and thread mode on targets
+/// without the A extension.
+struct RingBuffer<const N: usize> {
+    buf: UnsafeCell<[u8; N]>,
+    /// Next slot to write, only modified by the producer
+    head: AtomicUsize,
+    /// Next slot to read, only modified by the consumer
+    tail: AtomicUsize,
+}
+
+// SAFETY: producer and consumer never access the same slot at the same time
+unsafe impl<const N: usize> Sync for RingBuffer<N> {}
+
+impl<const N: usize> RingBuffer<N> {
+    const fn new() -> Self {
+        Self {
+            buf: UnsafeCell::new([0; N]),
+            head: AtomicUsize::new(0),
+            tail: AtomicUsize::new(0),
+        }
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        let head = self.head.load(Ordering::Acquire);
+        let tail = self.tail.load(Ordering::Acquire);
+        (head + N - tail) % N
+    }
+
+    #[inline]
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    #[inline]
+    fn is_full(&self) -> bool {
+        self.len() == N - 1
+    }
+
+    /// Producer side. Returns `false` if the buffer is full.
+    #[inline]
+    fn push(&self, byte: u8) -> bool {
+        let head = self.head.load(Ordering::Relaxed);
+        let next = (head + 1) % N;
+        if next == self.tail.load(Ordering::Acquire) {
+            return false;
+        }
+        unsafe { (*self.buf.get())[head] = byte };
+        self.head.store(next, Ordering::Release);
+        true
+    }
+
+    /// Consumer side
+    #[inline]
+    fn pop(&self) -> Option<u8> {
+        let tail = self.tail.load(Ordering::Relaxed);
+        if tail == self.head.load(Ordering::Acquire) {
+            return None;
+        }
+        let byte = unsafe { (*self.buf.get())[tail] };
+        self.tail.store((tail + 1) % N, Ordering::Release);
+        Some(byte)
+    }
+}
+
+/// Interrupt-driven, buffered driver for [ApbUart]
+pub struct BufferedUart {
+    uart: ApbUart,
+}
+
+impl BufferedUart {
+    /// Wraps an initialized UART and starts receiving into the RX buffer
+    ///
+    /// Call [BufferedUart::enable_interrupt] to start servicing the UART.
+    #[inline]
+    pub fn new(mut uart: ApbUart) -> Self {
+        uart.listen(UartInterrupt::OnData | UartInterrupt::OnError);
+        Self { uart }
+    }
+
+    /// Enables [Interrupt::Uart] on the CLIC with interrupt level `level`
+    ///
+    /// # Safety
+    ///
+    /// * Enabling an interrupt source can break mask-based critical sections.
+    #[inline]
+    pub unsafe fn enable_interrupt(&mut self, level: u8) {
+        Clic::configure(Interrupt::Uart)
+            .trigger(Trig::Level)
+            .polarity(Polarity::Pos)
+            .shv(true)
+            .level(level)
+            .enable()
+            .keep();
+    }
+
+    /// Stops servicing the UART and returns the blocking driver
+    ///
+    /// Bytes remaining in the TX buffer are discarded.
+    #[inline]
+    pub fn free(mut self) -> ApbUart {
+        Clic::ie(Interrupt::Uart).disable();
+        self.uart
+            .unlisten(UartInterrupt::OnData | UartInterrupt::OnEmpty | UartInterrupt::OnError);
+        while TX.pop().is_some() {}
+        self.uart
+    }
+}
+
+/// Records `err` to be reported by the next read
+#[inline]
+fn record_error(err: UartError) {
+    // Only called from the interrupt handler
+    RX_ERRORS.store(
+        RX_ERRORS.load(Ordering::Relaxed) | err as u8,
+        Ordering::Relaxed,
+    );
+}
+
+/// Takes the most significant pending line error
+#[inline]
+fn take_error() -> Option<UartError> {
+    riscv::interrupt::free(|| {
+        let errors = RX_ERRORS.load(Ordering::Relaxed);
+        RX_ERRORS.store(0, Ordering::Relaxed);
+        UartError::from_lsr(errors)
+    })
+}
+
+/// Moves bytes between the hardware FIFOs and the ring buffers
+///
+/// Call this from the `Uart` interrupt handler.
+#[inline]
+pub fn on_interrupt() {
+    // SAFETY: the interrupt handler has exclusive access to the FIFOs while
+    // BufferedUart is in use
+    let mut uart = unsafe { ApbUart::instance() };
+
+    // Drain RX FIFO
+    loop {
+        match uart.poll_rx() {
+            Ok(true) => {
+                let byte = unsafe { read_u8(UART_BASE + UART_RBR_THR_DLL_OFS) };
+                if !RX.push(byte) {
+                    record_error(UartError::Overrun);
+                }
+            }
+            Ok(false) => break,
+            Err(err) => record_error(err),
+        }
+    }
+
+    // Refill TX FIFO once it's empty
+    if uart.is_transmit_empty() {
+        for _ in 0..UART_FIFO_DEPTH {
+            match TX.pop() {
+                Some(byte) => unsafe { write_u8(UART_BASE + UART_RBR_THR_DLL_OFS, byte) },
+                None => break,
+            }
+        }
+        if TX.is_empty() {
+            uart.unlisten(UartInterrupt::OnEmpty);
+        }
+    }
+}
+
+impl embedded_io::ErrorType for BufferedUart {
+    type Error = UartError;
+}
+
+impl embedded_io::Read for BufferedUart {
+    /// Reads the bytes available in the RX buffer, blocking only while the
+    /// buffer is empty
+    ///
+    /// Returns a pending line error instead, if any. Buffered bytes are
+    /// returned by subsequent reads.
+    #[inline]
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        if let Some(err) = take_error() {
+            return Err(err);
+        }
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        while RX.is_empty() {}
+
+        let mut n = 0;
+        while n < buf.len() {
+            match RX.pop() {
+                Some(byte) => buf[n] = byte,
+                None => break,
+            }
+            n += 1;
+        }
+        Ok(n)
+    }
+}
+
+impl embedded_io::ReadReady for BufferedUart {
+    #[inline]
+    fn read_ready(&mut self) -> Result<bool, Self::Error> {
+        Ok(!RX.is_empty() || RX_ERRORS.load(Ordering::Relaxed) != 0)
+    }
+}
+
+impl embedded_io::Write for BufferedUart {
+    /// Copies as many bytes as fit into the TX buffer, blocking only while the
+    /// buffer is full
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        while TX.is_full() {}
+
+        let mut n = 0;
+        for &byte in buf {
+            if !TX.push(byte) {
+                break;
+            }
+            n += 1;
+        }
+
+        // Interrupt handler may unlisten concurrently
+        riscv::interrupt::free(|| self.uart.listen(UartInterrupt::OnEmpty));
+        Ok(n)
+    }
+
+    /// Blocks until the TX buffer and the hardware FIFO are empty
+    #[inline]
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        while !TX.is_empty() {}
+        self.uart.flush()
+    }
+}
+
+impl embedded_io::WriteReady for BufferedUart {
+    #[inline]
+    fn write_ready(&mut self) -> Result<bool, Self::Error> {
+        Ok(!TX.is_full())
+    }
+}
@@ -43,39 +43,6 @@ impl uDebug for crate::Interrupt {
     where
         W: ufmt::uWrite + ?Sized,
     {
-        f.write_str(match self {
-            crate::Interrupt::MachineSoft => "MachineSoft",
-            crate::Interrupt::MachineTimer => "MachineTimer",
-            crate::Interrupt::MachineExternal => "MachineExternal",
-            crate::Interrupt::Uart => "Uart",
-            crate::Interrupt::Gpio => "Gpio",
-            crate::Interrupt::SpiRxTxIrq => "SpiRxTxIrq",
-            crate::Interrupt::SpiEotIrq => "SpiEotIrq",
-            crate::Interrupt::Timer0Ovf => "Timer0Ovf",
-            crate::Interrupt::Timer0Cmp => "Timer0Cmp",
-            crate::Interrupt::Timer1Ovf => "Timer1Ovf",
-            crate::Interrupt::Timer1Cmp => "Timer1Cmp",
-            crate::Interrupt::Timer2Ovf => "Timer2Ovf",
-            crate::Interrupt::Timer2Cmp => "Timer2Cmp",
-            crate::Interrupt::Timer3Ovf => "Timer3Ovf",
-            crate::Interrupt::Timer3Cmp => "Timer3Cmp",
-            crate::Interrupt::Nmi => "Nmi",
-            crate::Interrupt::Dma0 => "Dma0",
-            crate::Interrupt::Dma1 => "Dma1",
-            crate::Interrupt::Dma2 => "Dma2",
-            crate::Interrupt::Dma3 => "Dma3",
-            crate::Interrupt::Dma4 => "Dma4",
-            crate::Interrupt::Dma5 => "Dma5",
-            crate::Interrupt::Dma6 => "Dma6",
-            crate::Interrupt::Dma7 => "Dma7",
-            crate::Interrupt::Dma8 => "Dma8",
-            crate::Interrupt::Dma9 => "Dma9",
-            crate::Interrupt::Dma10 => "Dma10",
-            crate::Interrupt::Dma11 => "Dma11",
-            crate::Interrupt::Dma12 => "Dma12",
-            crate::Interrupt::Dma13 => "Dma13",
-            crate::Interrupt::Dma14 => "Dma14",
-            crate::Interrupt::Dma15 => "Dma15",
-        })
+        f.write_str(self.name())
     }
 }
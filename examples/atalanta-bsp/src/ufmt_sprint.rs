@@ -1,14 +1,19 @@
 use crate::uart::ApbUartHal;
 
+/// Writes formatted output to the registered [crate::sink::Sink]
 #[macro_export]
 macro_rules! sprint {
     ($s:expr) => {{
         use $crate::ufmt;
-        ufmt::uwrite!($crate::uart::ApbUartHal::<{ $crate::mmap::UART_BASE }> {}, $s).unwrap()
+        $crate::sink::with_sink(|sink| {
+            ufmt::uwrite!($crate::sink::SinkWriter(sink), $s).unwrap()
+        })
     }};
     ($($tt:tt)*) => {{
         use $crate::ufmt;
-        ufmt::uwrite!($crate::uart::ApbUartHal::<{ $crate::mmap::UART_BASE }>, $($tt)*).unwrap()
+        $crate::sink::with_sink(|sink| {
+            ufmt::uwrite!($crate::sink::SinkWriter(sink), $($tt)*).unwrap()
+        })
     }};
 }
 
@@ -20,9 +25,14 @@ macro_rules! sprintln {
     }};
     // IMPORTANT use `tt` fragments instead of `expr` fragments (i.e. `$($exprs:expr),*`)
     ($($tt:tt)*) => {{
-        use $crate::sprint;
-        sprint!($($tt)*);
-        sprint!("\r\n");
+        use $crate::ufmt;
+        // Write text and newline under one claim of the sink so that they
+        // aren't torn apart
+        $crate::sink::with_sink(|sink| {
+            let mut w = $crate::sink::SinkWriter(sink);
+            ufmt::uwrite!(w, $($tt)*).unwrap();
+            ufmt::uwrite!(w, "\r\n").unwrap()
+        })
     }};
 }
 
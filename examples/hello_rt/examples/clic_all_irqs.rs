@@ -11,8 +11,8 @@ use bsp::{
     riscv,
     rt::entry,
     sprintln, tb,
-    uart::ApbUart,
-    Interrupt,
+    uart::{ApbUart, UartConfig},
+    Interrupt, Peripherals,
 };
 use hello_rt::{print_example_name, setup_irq, tear_irq, UART_BAUD};
 
@@ -48,7 +48,8 @@ static mut IRQ_RECVD: u64 = 0;
 /// Example entry point
 #[entry]
 fn main() -> ! {
-    let mut serial = ApbUart::init(bsp::CPU_FREQ, UART_BAUD);
+    let p = Peripherals::take().unwrap();
+    let mut serial = ApbUart::new(p.uart, bsp::CPU_FREQ, UartConfig::new(UART_BAUD));
     print_example_name!();
 
     // Set level bits to 8
@@ -0,0 +1,43 @@
+//! Prints the parameterization of the CLIC and checks that all interrupts used
+//! by the BSP are implemented
+#![no_main]
+#![no_std]
+
+use bsp::{
+    clic::{Clic, InterruptNumber},
+    rt::entry,
+    sprintln, tb,
+    uart::{ApbUart, UartConfig},
+    Interrupt, Peripherals,
+};
+use hello_rt::{print_example_name, UART_BAUD};
+
+#[entry]
+fn main() -> ! {
+    let p = Peripherals::take().unwrap();
+    let mut serial = ApbUart::new(p.uart, bsp::CPU_FREQ, UartConfig::new(UART_BAUD));
+    print_example_name!();
+
+    let info = Clic::info();
+    sprintln!("{:?}", info);
+    sprintln!(
+        "level bits: {}, priority bits: {}",
+        Clic::ctl(Interrupt::MachineTimer).level_bits(),
+        Clic::ctl(Interrupt::MachineTimer).priority_bits()
+    );
+
+    let mut ok = true;
+    for irq in Interrupt::ALL {
+        if Clic::try_configure(*irq).is_err() {
+            sprintln!("interrupt {} is not implemented", irq.number());
+            ok = false;
+        }
+    }
+
+    if ok {
+        tb::signal_pass(Some(&mut serial))
+    } else {
+        tb::signal_fail(Some(&mut serial))
+    }
+    loop {}
+}
@@ -17,8 +17,8 @@ use bsp::{
     riscv,
     rt::entry,
     sprintln, tb,
-    uart::ApbUart,
-    write_u32, Interrupt, CPU_FREQ,
+    uart::{ApbUart, UartConfig},
+    write_u32, Interrupt, Peripherals, CPU_FREQ,
 };
 use hello_rt::{print_example_name, setup_irq, tear_irq, UART_BAUD};
 
@@ -29,7 +29,8 @@ static mut LOCK: bool = true;
 /// Example entry point
 #[entry]
 fn main() -> ! {
-    let mut serial = ApbUart::init(bsp::CPU_FREQ, UART_BAUD);
+    let p = Peripherals::take().unwrap();
+    let mut serial = ApbUart::new(p.uart, bsp::CPU_FREQ, UartConfig::new(UART_BAUD));
     print_example_name!();
 
     // Set level bits to 8
@@ -74,8 +75,11 @@ fn wait_on_lock() {
         if !unsafe { ptr::read_volatile(ptr::addr_of_mut!(LOCK)) } {
             break;
         }
-        led_off(leds[0]);
-        led_on(leds[1]);
+        // SAFETY: `Gpio` is never split, no pins are owned
+        unsafe {
+            led_off(leds[0]);
+            led_on(leds[1]);
+        }
         asm_delay(delay);
     }
 }
@@ -85,6 +89,7 @@ fn custom_interrupt_handler() {
     unsafe { Clic::ip(MTIMER_IRQ).unpend() };
 
     // Flip a led and unlock the lock
-    led_toggle(Led::Ld0);
+    // SAFETY: `Gpio` is never split, no pins are owned
+    unsafe { led_toggle(Led::Ld0) };
     unsafe { ptr::write_volatile(ptr::addr_of_mut!(LOCK), false) };
 }
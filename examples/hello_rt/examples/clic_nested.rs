@@ -18,8 +18,8 @@ use bsp::{
     rt::entry,
     sprint, sprintln,
     tb::{signal_fail, signal_pass},
-    uart::ApbUart,
-    Interrupt,
+    uart::{ApbUart, UartConfig},
+    Interrupt, Peripherals,
 };
 use hello_rt::{print_example_name, tear_irq, UART_BAUD};
 
@@ -27,7 +27,8 @@ static mut LOCK: u8 = 0;
 
 #[entry]
 fn main() -> ! {
-    let mut serial = ApbUart::init(bsp::CPU_FREQ, UART_BAUD);
+    let p = Peripherals::take().unwrap();
+    let mut serial = ApbUart::new(p.uart, bsp::CPU_FREQ, UartConfig::new(UART_BAUD));
     print_example_name!();
 
     // Set level bits to 8
@@ -0,0 +1,87 @@
+//! Tests that `with_threshold` holds off low-level interrupts only
+#![no_main]
+#![no_std]
+
+use core::ptr;
+
+use bsp::{
+    clic::{Clic, Polarity, Trig},
+    interrupt::with_threshold,
+    riscv,
+    rt::entry,
+    sprintln,
+    tb::{signal_fail, signal_pass},
+    uart::{ApbUart, UartConfig},
+    Interrupt, Peripherals,
+};
+use hello_rt::{print_example_name, tear_irq, UART_BAUD};
+
+const LO: Interrupt = Interrupt::Dma0;
+const HI: Interrupt = Interrupt::Dma1;
+
+static mut LO_DONE: bool = false;
+static mut HI_DONE: bool = false;
+
+#[entry]
+fn main() -> ! {
+    let p = Peripherals::take().unwrap();
+    let mut serial = ApbUart::new(p.uart, bsp::CPU_FREQ, UartConfig::new(UART_BAUD));
+    print_example_name!();
+
+    // Set level bits to 8
+    Clic::smclicconfig().set_mnlbits(8);
+    setup_irq(LO, 0x40);
+    setup_irq(HI, 0xc0);
+    unsafe { riscv::interrupt::enable() };
+
+    let (lo_inside, hi_inside) = with_threshold(0x80, || unsafe {
+        Clic::ip(LO).pend();
+        Clic::ip(HI).pend();
+        (
+            ptr::read_volatile(ptr::addr_of!(LO_DONE)),
+            ptr::read_volatile(ptr::addr_of!(HI_DONE)),
+        )
+    });
+    let lo_after = unsafe { ptr::read_volatile(ptr::addr_of!(LO_DONE)) };
+    sprintln!(
+        "inside: lo {}, hi {}; after: lo {}",
+        lo_inside,
+        hi_inside,
+        lo_after
+    );
+
+    riscv::interrupt::disable();
+    tear_irq(LO);
+    tear_irq(HI);
+
+    if !lo_inside && hi_inside && lo_after {
+        signal_pass(Some(&mut serial))
+    } else {
+        signal_fail(Some(&mut serial))
+    }
+    loop {}
+}
+
+#[export_name = "DefaultHandler"]
+fn interrupt_handler() {
+    let irq = (riscv::register::mcause::read().bits() & 0xfff) as u16;
+    unsafe {
+        if irq == LO as u16 {
+            ptr::write_volatile(ptr::addr_of_mut!(LO_DONE), true);
+        } else if irq == HI as u16 {
+            ptr::write_volatile(ptr::addr_of_mut!(HI_DONE), true);
+        }
+    }
+}
+
+fn setup_irq(irq: Interrupt, level: u8) {
+    unsafe {
+        Clic::configure(irq)
+            .trigger(Trig::Edge)
+            .polarity(Polarity::Pos)
+            .shv(true)
+            .level(level)
+            .enable()
+            .keep()
+    };
+}
@@ -0,0 +1,52 @@
+//! Halts into the debugger whenever [IRQ] is taken, using a CLIC interrupt
+//! trigger
+//!
+//! Run under GDB: the hart enters Debug Mode on each interrupt, before the
+//! handler runs.
+#![no_main]
+#![no_std]
+
+use bsp::{
+    clic::{Clic, TriggerAction},
+    riscv,
+    rt::entry,
+    sprintln, tb,
+    uart::{ApbUart, UartConfig},
+    Interrupt, Peripherals,
+};
+use hello_rt::{print_example_name, setup_irq, tear_irq, UART_BAUD};
+
+const IRQ: Interrupt = Interrupt::MachineSoft;
+const TRIGGER: usize = 0;
+
+#[entry]
+fn main() -> ! {
+    let p = Peripherals::take().unwrap();
+    let mut serial = ApbUart::new(p.uart, bsp::CPU_FREQ, UartConfig::new(UART_BAUD));
+    print_example_name!();
+
+    sprintln!("{:?}", Clic::info());
+    if let Err(e) = Clic::set_trigger(TRIGGER, IRQ, TriggerAction::DebugMode) {
+        sprintln!("cannot set trigger: {:?}", e);
+        tb::signal_fail(Some(&mut serial));
+        loop {}
+    }
+
+    // Set level bits to 8
+    Clic::smclicconfig().set_mnlbits(8);
+    setup_irq(IRQ);
+    unsafe { riscv::interrupt::enable() };
+
+    // Halts into the debugger
+    unsafe { Clic::ip(IRQ).pend() };
+
+    tear_irq(IRQ);
+    Clic::clear_trigger(TRIGGER);
+    tb::signal_pass(Some(&mut serial));
+    loop {}
+}
+
+#[export_name = "DefaultHandler"]
+fn interrupt_handler() {
+    sprintln!("IRQ handled");
+}
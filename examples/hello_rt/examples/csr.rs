@@ -9,6 +9,7 @@ use bsp::{
     riscv::asm::wfi,
     rt::entry,
     uart::*,
+    Peripherals,
     CPU_FREQ,
 };
 use hello_rt::{print_example_name, UART_BAUD};
@@ -27,7 +28,8 @@ macro_rules! print_csr {
 
 #[entry]
 fn main() -> ! {
-    let _serial = ApbUart::init(CPU_FREQ, UART_BAUD);
+    let p = Peripherals::take().unwrap();
+    let _serial = ApbUart::new(p.uart, CPU_FREQ, UartConfig::new(UART_BAUD));
 
     print_example_name!();
 
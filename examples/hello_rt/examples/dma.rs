@@ -0,0 +1,74 @@
+//! Move a buffer from DMEM to SRAM using the DMA and compare the contents.
+//! Completion is signaled by the DMA interrupt.
+#![no_main]
+#![no_std]
+#![allow(static_mut_refs)]
+#![allow(non_snake_case)]
+
+use core::ptr;
+
+use bsp::{
+    clic::Clic,
+    dma::{self, Dma},
+    riscv,
+    rt::{entry, interrupt},
+    sprintln,
+    tb::{signal_fail, signal_pass},
+    uart::*,
+    Peripherals, CPU_FREQ,
+};
+use hello_rt::{print_example_name, UART_BAUD};
+
+const LEN: usize = 8;
+
+/// Zero-initialized, i.e., placed in .bss (DMEM)
+static mut SRC: [u32; LEN] = [0; LEN];
+/// Non-zero initialized, i.e., placed in .data (SRAM)
+static mut DST: [u32; LEN] = [u32::MAX; LEN];
+
+#[entry]
+fn main() -> ! {
+    let p = Peripherals::take().unwrap();
+    let mut serial = ApbUart::new(p.uart, CPU_FREQ, UartConfig::new(UART_BAUD));
+    print_example_name!();
+
+    // Set level bits to 8
+    Clic::smclicconfig().set_mnlbits(8);
+
+    // (pseudo)random data generation
+    let mut lfsr = 0xBEEF_FACEu32;
+    for word in unsafe { SRC.iter_mut() } {
+        let bit = (lfsr ^ (lfsr >> 2) ^ (lfsr >> 3) ^ (lfsr >> 5)) & 1;
+        lfsr = (lfsr >> 1) | (bit << 31);
+        *word = lfsr;
+    }
+
+    let mut dma = Dma::new(p.dma);
+    unsafe {
+        dma.listen(0x88);
+        riscv::interrupt::enable();
+    }
+
+    // SAFETY: the buffers are only referenced here
+    let (src, dst) = unsafe { (&*ptr::addr_of!(SRC), &mut *ptr::addr_of_mut!(DST)) };
+
+    sprintln!("dispatching DMA transfer...");
+    let transfer = dma.copy(src, dst).unwrap();
+    let (src, dst) = transfer.wait();
+
+    riscv::interrupt::disable();
+    dma.unlisten();
+
+    if src == dst {
+        signal_pass(Some(&mut serial));
+    } else {
+        sprintln!("SRC-DST mismatch");
+        signal_fail(Some(&mut serial));
+    }
+    loop {}
+}
+
+#[interrupt]
+fn Dma0() {
+    dma::on_interrupt();
+}
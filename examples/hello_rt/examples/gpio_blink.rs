@@ -6,19 +6,23 @@
 use core::arch;
 
 use bsp::gpio::GpioLo;
-use bsp::{rt::entry, uart::*, CPU_FREQ};
+use bsp::{rt::entry, uart::*, Peripherals, CPU_FREQ};
 use bsp::{sprintln, NOPS_PER_SEC};
 use hello_rt::UART_BAUD;
 
 #[entry]
 fn main() -> ! {
-    let _serial = ApbUart::init(CPU_FREQ, UART_BAUD);
+    let p = Peripherals::take().unwrap();
+    let _serial = ApbUart::new(p.uart, CPU_FREQ, UartConfig::new(UART_BAUD));
 
     sprintln!("[gpio_blink]");
 
     // Enable clocks and set as output for gpios 0..=3
-    GpioLo::en(0xf);
-    GpioLo::set_output(0xf);
+    // SAFETY: `Gpio` is never split, no pins are owned
+    unsafe {
+        GpioLo::en(0xf);
+        GpioLo::set_output(0xf);
+    }
 
     loop {
         unsafe {
@@ -0,0 +1,49 @@
+//! Toggle leds Ld2 & Ld3 on button presses on GPIOs 1..=2 using interrupts
+#![no_main]
+#![no_std]
+#![allow(non_snake_case)]
+
+use bsp::{
+    clic::Clic,
+    gpio::{self, Edge, Gpio},
+    led::{led_toggle, Led},
+    riscv::{self, asm::wfi},
+    rt::{entry, interrupt},
+    uart::*,
+    Peripherals, CPU_FREQ,
+};
+use hello_rt::{print_example_name, UART_BAUD};
+
+#[entry]
+fn main() -> ! {
+    let p = Peripherals::take().unwrap();
+    let _serial = ApbUart::new(p.uart, CPU_FREQ, UartConfig::new(UART_BAUD));
+    print_example_name!();
+
+    // Set level bits to 8
+    Clic::smclicconfig().set_mnlbits(8);
+
+    let pins = Gpio::new(p.gpio_lo, p.gpio_hi).split();
+    let mut btn0 = pins.p1.into_input();
+    let mut btn1 = pins.p2.into_input();
+    btn0.listen(Edge::Rising);
+    btn1.listen(Edge::Both);
+
+    // SAFETY: the led pins 16 & 24 are not taken from `pins`
+    gpio::set_handler(&btn0, Some(|| unsafe { led_toggle(Led::Ld2) }));
+    gpio::set_handler(&btn1, Some(|| unsafe { led_toggle(Led::Ld3) }));
+
+    unsafe {
+        gpio::enable_interrupt(0x88);
+        riscv::interrupt::enable();
+    }
+
+    loop {
+        wfi();
+    }
+}
+
+#[interrupt]
+fn Gpio() {
+    gpio::dispatch();
+}
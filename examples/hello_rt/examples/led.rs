@@ -2,21 +2,26 @@
 #![no_main]
 #![no_std]
 
-use bsp::{asm_delay, led::*, rt::entry, NOPS_PER_SEC};
+use bsp::{embedded_hal::delay::DelayNs, led::*, rt::entry, timer_group::Timer, Peripherals};
 
 #[inline(never)]
-fn blinky() {
+fn blinky(delay: &mut impl DelayNs) {
     let ord = [Led::Ld3, Led::Ld0, Led::Ld1, Led::Ld2, Led::Ld3].windows(2);
-    let delay = NOPS_PER_SEC / ord.len() as u32;
+    let delay_ms = 1000 / ord.len() as u32;
     for leds in ord.cycle() {
-        led_off(leds[0]);
-        led_on(leds[1]);
-        asm_delay(delay);
+        // SAFETY: `Gpio` is never split, no pins are owned
+        unsafe {
+            led_off(leds[0]);
+            led_on(leds[1]);
+        }
+        delay.delay_ms(delay_ms);
     }
 }
 
 #[entry]
 fn main() -> ! {
-    blinky();
+    let p = Peripherals::take().unwrap();
+    let mut delay = Timer::from(p.timer0).into_sleeping_delay();
+    blinky(&mut delay);
     unreachable!()
 }
@@ -6,7 +6,13 @@
 #![no_main]
 #![no_std]
 
-use bsp::{riscv, rt::entry, sprintln, tb, uart::ApbUart};
+use bsp::{
+    riscv,
+    rt::entry,
+    sprintln, tb,
+    uart::{ApbUart, UartConfig},
+    Peripherals,
+};
 use hello_rt::{clic::*, print_example_name, UART_BAUD};
 
 // 3 == MSI
@@ -17,7 +23,8 @@ static mut LAST_IRQ: Option<u32> = None;
 /// Example entry point
 #[entry]
 fn main() -> ! {
-    let mut serial = ApbUart::init(bsp::CPU_FREQ, UART_BAUD);
+    let p = Peripherals::take().unwrap();
+    let mut serial = ApbUart::new(p.uart, bsp::CPU_FREQ, UartConfig::new(UART_BAUD));
     print_example_name!();
 
     // Set level bits to 8
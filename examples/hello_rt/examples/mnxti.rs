@@ -0,0 +1,98 @@
+//! Services three chained interrupts back-to-back using the software vectored
+//! `mnxti` trap
+//!
+//! Compare the cycle count against `clic_nested` and `trampolines`.
+#![no_main]
+#![no_std]
+#![allow(non_snake_case)]
+
+use core::ptr;
+
+use bsp::{
+    clic::{Clic, Polarity, Trig},
+    riscv::{self, register::mcycle},
+    rt::{entry, interrupt},
+    sprintln,
+    tb::{signal_fail, signal_pass},
+    uart::{ApbUart, UartConfig},
+    Interrupt, Peripherals,
+};
+use hello_rt::{print_example_name, tear_irq, UART_BAUD};
+
+const IRQS: [Interrupt; 3] = [Interrupt::Dma0, Interrupt::Dma1, Interrupt::Timer3Cmp];
+
+static mut COUNT: u8 = 0;
+
+#[entry]
+fn main() -> ! {
+    let p = Peripherals::take().unwrap();
+    let mut serial = ApbUart::new(p.uart, bsp::CPU_FREQ, UartConfig::new(UART_BAUD));
+    print_example_name!();
+
+    // Set level bits to 8
+    Clic::smclicconfig().set_mnlbits(8);
+    Clic::set_nxti_forwarding(true);
+
+    for irq in IRQS {
+        unsafe {
+            Clic::configure(irq)
+                .trigger(Trig::Edge)
+                .polarity(Polarity::Pos)
+                .shv(false)
+                .level(0x88)
+                .enable()
+                .keep()
+        };
+    }
+
+    let (start, end) = unsafe {
+        // Hold off the interrupts until all of them are pending
+        bsp::register::mintthresh::write(0xff.into());
+        riscv::interrupt::enable();
+        for irq in IRQS {
+            Clic::ip(irq).pend();
+        }
+
+        let start = mcycle::read();
+        bsp::register::mintthresh::write(0x0.into());
+        while ptr::read_volatile(ptr::addr_of!(COUNT)) != IRQS.len() as u8 {}
+        (start, mcycle::read())
+    };
+    riscv::interrupt::disable();
+    sprintln!("serviced {} interrupts in {} cycles", IRQS.len(), end.wrapping_sub(start));
+
+    Clic::set_nxti_forwarding(false);
+    for irq in IRQS {
+        tear_irq(irq);
+    }
+
+    if unsafe { ptr::read_volatile(ptr::addr_of!(COUNT)) } == IRQS.len() as u8 {
+        signal_pass(Some(&mut serial))
+    } else {
+        signal_fail(Some(&mut serial))
+    }
+    loop {}
+}
+
+#[inline]
+fn count() {
+    unsafe {
+        let count = ptr::read_volatile(ptr::addr_of!(COUNT));
+        ptr::write_volatile(ptr::addr_of_mut!(COUNT), count + 1);
+    }
+}
+
+#[interrupt]
+fn Dma0() {
+    count();
+}
+
+#[interrupt]
+fn Dma1() {
+    count();
+}
+
+#[interrupt]
+fn Timer3Cmp() {
+    count();
+}
@@ -0,0 +1,52 @@
+//! Checks that the monotonic clock advances and that delays take at least as
+//! long as requested
+#![no_main]
+#![no_std]
+
+use bsp::{
+    mtimer::{Duration, MTimer, Monotonic},
+    rt::entry,
+    sprintln, tb,
+    uart::{ApbUart, UartConfig},
+    Peripherals, CPU_FREQ,
+};
+use hello_rt::{print_example_name, UART_BAUD};
+
+const DELAY: Duration = Duration::micros(100);
+
+#[entry]
+fn main() -> ! {
+    let p = Peripherals::take().unwrap();
+    let mut serial = ApbUart::new(p.uart, CPU_FREQ, UartConfig::new(UART_BAUD));
+    print_example_name!();
+
+    Monotonic::start(MTimer::new(p.mtimer));
+
+    let mut ok = true;
+    let mut prev = Monotonic::now();
+    for _ in 0..10 {
+        let start = Monotonic::now();
+        if start < prev {
+            sprintln!("clock went backwards: {} < {}", start.ticks(), prev.ticks());
+            ok = false;
+        }
+        Monotonic::delay(DELAY);
+        let elapsed = Monotonic::elapsed(start);
+        sprintln!(
+            "delay {} us took {} us",
+            DELAY.to_micros(),
+            elapsed.to_micros()
+        );
+        if elapsed < DELAY {
+            ok = false;
+        }
+        prev = start;
+    }
+
+    if ok {
+        tb::signal_pass(Some(&mut serial))
+    } else {
+        tb::signal_fail(Some(&mut serial))
+    }
+    loop {}
+}
@@ -12,8 +12,8 @@ use bsp::{
     rt::{entry, interrupt},
     sprintln,
     timer_group::Timer,
-    uart::ApbUart,
-    Interrupt, CPU_FREQ,
+    uart::{ApbUart, UartConfig},
+    Interrupt, Peripherals, CPU_FREQ,
 };
 use fugit::ExtU64;
 use hello_rt::{print_example_name, setup_irq, tear_irq, UART_BAUD};
@@ -24,16 +24,16 @@ const PERIPH_CLK_DIV: u32 = 2;
 
 #[entry]
 fn main() -> ! {
-    let mut serial = ApbUart::init(CPU_FREQ, UART_BAUD);
+    let p = Peripherals::take().unwrap();
+    let mut serial = ApbUart::new(p.uart, CPU_FREQ, UartConfig::new(UART_BAUD));
     print_example_name!();
 
     // Set level bits to 8
     Clic::smclicconfig().set_mnlbits(8);
-
-    let mut mtimer = MTimer::instance().into_oneshot();
+    let mut mtimer = MTimer::new(p.mtimer).into_oneshot();
     setup_irq(Interrupt::MachineTimer);
 
-    let mut t0 = Timer::init::<TIMER0_ADDR>();
+    let mut t0 = Timer::from(p.timer0);
     setup_irq(Interrupt::Timer0Cmp);
 
     unsafe {
@@ -11,8 +11,8 @@ use bsp::{
     riscv::{self, asm::wfi},
     rt::{entry, interrupt},
     sprintln,
-    uart::ApbUart,
-    Interrupt, CPU_FREQ, NOPS_PER_SEC,
+    uart::{ApbUart, UartConfig},
+    Interrupt, Peripherals, CPU_FREQ, NOPS_PER_SEC,
 };
 use heapless::Vec;
 use hello_rt::{print_example_name, setup_irq, tear_irq, UART_BAUD};
@@ -28,14 +28,15 @@ static mut STOP: bool = false;
 
 #[entry]
 fn main() -> ! {
-    let mut serial = ApbUart::init(CPU_FREQ, UART_BAUD);
+    let p = Peripherals::take().unwrap();
+    let mut serial = ApbUart::new(p.uart, CPU_FREQ, UartConfig::new(UART_BAUD));
     print_example_name!();
 
     // Set level bits to 8
     Clic::smclicconfig().set_mnlbits(8);
 
     // Set a timer to trigger an interrupt every `ÌNTERVAL`
-    let mut mtimer = MTimer::instance();
+    let mut mtimer = MTimer::new(p.mtimer);
     setup_irq(Interrupt::MachineTimer);
     unsafe {
         let counter = mtimer.counter();
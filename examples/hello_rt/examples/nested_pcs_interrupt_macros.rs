@@ -16,8 +16,8 @@ use bsp::{
     rt::entry,
     sprint, sprintln,
     tb::{signal_fail, signal_pass},
-    uart::ApbUart,
-    unmask_u32, Interrupt,
+    uart::{ApbUart, UartConfig},
+    unmask_u32, Interrupt, Peripherals,
 };
 use hello_rt::{function, print_example_name, tear_irq, UART_BAUD};
 
@@ -41,7 +41,8 @@ fn disable_pcs(irq: Interrupt) {
 
 #[entry]
 fn main() -> ! {
-    let mut serial = ApbUart::init(bsp::CPU_FREQ, UART_BAUD);
+    let p = Peripherals::take().unwrap();
+    let mut serial = ApbUart::new(p.uart, bsp::CPU_FREQ, UartConfig::new(UART_BAUD));
     print_example_name!();
 
     // This test found an edge case with PCS mret when run twice; therefore we keep
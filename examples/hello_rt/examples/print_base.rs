@@ -5,13 +5,20 @@
 #![no_main]
 #![no_std]
 
-use bsp::{mmap::CLIC_BASE_ADDR, rt::entry, sprintln, uart::ApbUart};
+use bsp::{
+    mmap::CLIC_BASE_ADDR,
+    rt::entry,
+    sprintln,
+    uart::{ApbUart, UartConfig},
+    Peripherals,
+};
 use hello_rt::UART_BAUD;
 
 /// Example entry point
 #[entry]
 fn main() -> ! {
-    ApbUart::init(bsp::CPU_FREQ, UART_BAUD);
+    let p = Peripherals::take().unwrap();
+    ApbUart::new(p.uart, bsp::CPU_FREQ, UartConfig::new(UART_BAUD));
 
     let clic_base = CLIC_BASE_ADDR;
 
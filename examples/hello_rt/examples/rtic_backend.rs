@@ -0,0 +1,76 @@
+//! Hand-expanded RTIC application using the CLIC backend
+//!
+//! Two software tasks run on dispatchers at priorities 1 and 2 and share a
+//! counter with a ceiling of 2. This is roughly what RTIC generates for
+//! `#[task(priority = N, shared = [counter])]`.
+#![no_main]
+#![no_std]
+#![allow(non_snake_case)]
+
+use core::ptr;
+
+use bsp::{
+    asm_delay, nested_interrupt, riscv,
+    rt::entry,
+    rtic::{self, Peripherals, DISPATCHERS},
+    sprintln,
+    tb::{signal_fail, signal_pass},
+    uart::{ApbUart, UartConfig},
+    CPU_FREQ,
+};
+use hello_rt::{print_example_name, UART_BAUD};
+
+const LO_PRIO: u8 = 1;
+const HI_PRIO: u8 = 2;
+const COUNTER_CEILING: u8 = HI_PRIO;
+
+static mut COUNTER: u32 = 0;
+
+#[entry]
+fn main() -> ! {
+    // `init`
+    let device = Peripherals::take().unwrap();
+    let mut serial = ApbUart::new(device.uart, CPU_FREQ, UartConfig::new(UART_BAUD));
+    print_example_name!();
+    unsafe {
+        rtic::init();
+        rtic::enable_dispatcher(DISPATCHERS[0], LO_PRIO, false);
+        rtic::enable_dispatcher(DISPATCHERS[1], HI_PRIO, false);
+        riscv::interrupt::enable();
+    }
+
+    // `idle`, spawns the low priority task
+    rtic::pend(DISPATCHERS[0]);
+    asm_delay(1000);
+
+    let count = unsafe { rtic::lock(ptr::addr_of_mut!(COUNTER), COUNTER_CEILING, |c| *c) };
+    sprintln!("count: {}", count);
+
+    riscv::interrupt::disable();
+    if count == 2 {
+        signal_pass(Some(&mut serial))
+    } else {
+        signal_fail(Some(&mut serial))
+    }
+    loop {}
+}
+
+/// Dispatcher for priority 1
+#[nested_interrupt]
+fn Dma1() {
+    rtic::run(LO_PRIO, || unsafe {
+        rtic::lock(ptr::addr_of_mut!(COUNTER), COUNTER_CEILING, |c| {
+            *c += 1;
+            // Spawn the high priority task, which runs after the lock is
+            // released
+            rtic::pend(DISPATCHERS[1]);
+        })
+    });
+}
+
+/// Dispatcher for priority 2
+#[nested_interrupt]
+fn Dma2() {
+    // Priority equals ceiling, no lock needed
+    rtic::run(HI_PRIO, || unsafe { *ptr::addr_of_mut!(COUNTER) += 1 });
+}
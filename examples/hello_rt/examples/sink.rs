@@ -0,0 +1,37 @@
+//! Redirects `sprint!` output into RAM and replays it over UART
+#![no_main]
+#![no_std]
+
+use core::ptr;
+
+use bsp::{
+    rt::entry,
+    sink::{self, NullSink, RamSink},
+    sprintln,
+    uart::*,
+    Peripherals, CPU_FREQ,
+};
+use hello_rt::{print_example_name, UART_BAUD};
+
+static mut CAPTURE: RamSink<64> = RamSink::new();
+static mut NULL: NullSink = NullSink;
+
+#[entry]
+fn main() -> ! {
+    let p = Peripherals::take().unwrap();
+    let mut serial = ApbUart::new(p.uart, CPU_FREQ, UartConfig::new(UART_BAUD));
+    print_example_name!();
+
+    sink::set_sink(unsafe { &mut *ptr::addr_of_mut!(CAPTURE) });
+    sprintln!("captured in RAM");
+
+    sink::set_sink(unsafe { &mut *ptr::addr_of_mut!(NULL) });
+    sprintln!("discarded");
+
+    // Revert to UART & replay the captured output
+    sink::take_sink();
+    let captured = unsafe { (*ptr::addr_of!(CAPTURE)).as_bytes() };
+    serial.write_str(unsafe { core::str::from_utf8_unchecked(captured) });
+
+    loop {}
+}
@@ -0,0 +1,56 @@
+//! Read the JEDEC ID of a flash attached to chip select 0, then dispatch a
+//! non-blocking write and wait for the end of transmission interrupt.
+#![no_main]
+#![no_std]
+#![allow(non_snake_case)]
+
+use bsp::{
+    clic::Clic,
+    embedded_hal::spi::{Operation, SpiDevice},
+    riscv,
+    rt::{entry, interrupt},
+    spi::{self, ApbSpi, ChipSelect},
+    sprintln,
+    tb::signal_pass,
+    uart::*,
+    Peripherals, CPU_FREQ,
+};
+use hello_rt::{print_example_name, UART_BAUD};
+
+const SPI_SCK: u32 = 1_000_000;
+
+#[entry]
+fn main() -> ! {
+    let p = Peripherals::take().unwrap();
+    let mut serial = ApbUart::new(p.uart, CPU_FREQ, UartConfig::new(UART_BAUD));
+    print_example_name!();
+
+    // Set level bits to 8
+    Clic::smclicconfig().set_mnlbits(8);
+
+    let mut spi = ApbSpi::new(p.spi, CPU_FREQ, SPI_SCK);
+    spi.select(ChipSelect::Cs0);
+
+    // Read JEDEC ID (0x9F), the opcode is sent as the command phase
+    let mut id = [0u8; 3];
+    spi.transaction(&mut [Operation::Write(&[0x9F]), Operation::Read(&mut id)])
+        .unwrap();
+    sprintln!("JEDEC ID: {:x} {:x} {:x}", id[0], id[1], id[2]);
+
+    unsafe {
+        spi.listen(0x88);
+        riscv::interrupt::enable();
+    }
+    spi.start_write(&[0xde, 0xad, 0xbe, 0xef]).unwrap();
+    while !spi.is_done() {}
+    riscv::interrupt::disable();
+    spi.unlisten();
+
+    signal_pass(Some(&mut serial));
+    loop {}
+}
+
+#[interrupt]
+fn SpiEotIrq() {
+    spi::on_interrupt();
+}
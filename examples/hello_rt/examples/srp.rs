@@ -0,0 +1,110 @@
+//! Shares a counter between thread mode and two timer tasks at different CLIC
+//! levels using SRP resources
+#![no_main]
+#![no_std]
+#![allow(non_snake_case)]
+
+use bsp::{
+    clic::{Clic, Polarity, Trig},
+    mmap::apb_timer::{TIMER0_ADDR, TIMER1_ADDR},
+    nested_interrupt, riscv,
+    rt::entry,
+    sprintln,
+    srp::{Resource, Task},
+    tb::{signal_fail, signal_pass},
+    timer_group::Timer,
+    uart::{ApbUart, UartConfig},
+    Interrupt, Peripherals, NOPS_PER_SEC,
+};
+use hello_rt::{print_example_name, tear_irq, UART_BAUD};
+
+const LO_LEVEL: u8 = 0x40;
+const HI_LEVEL: u8 = 0x80;
+const TICKS: u32 = 10;
+const INTERVAL: u32 = if cfg!(feature = "rtl-tb") {
+    0x100
+} else {
+    NOPS_PER_SEC / 100
+};
+
+/// Accessed by all tasks, ceiling is the highest of them
+static COUNTER: Resource<u32, HI_LEVEL> = Resource::new(0);
+/// Ticks remaining per timer, accessed by the timer tasks only
+static TICKS_LEFT: Resource<[u32; 2], HI_LEVEL> = Resource::new([TICKS; 2]);
+
+#[entry]
+fn main() -> ! {
+    let p = Peripherals::take().unwrap();
+    let mut serial = ApbUart::new(p.uart, bsp::CPU_FREQ, UartConfig::new(UART_BAUD));
+    print_example_name!();
+    // SAFETY: thread mode runs at level 0
+    let task = unsafe { Task::<0>::new() };
+
+    // Set level bits to 8
+    Clic::smclicconfig().set_mnlbits(8);
+    setup_irq(Interrupt::Timer0Cmp, LO_LEVEL);
+    setup_irq(Interrupt::Timer1Cmp, HI_LEVEL);
+
+    let mut timer0 = Timer::from(p.timer0);
+    let mut timer1 = Timer::from(p.timer1);
+    timer0.set_cmp(INTERVAL);
+    timer1.set_cmp(INTERVAL / 3);
+    timer0.enable();
+    timer1.enable();
+    unsafe { riscv::interrupt::enable() };
+
+    while COUNTER.lock(&task, |c| *c) < 2 * TICKS {}
+
+    riscv::interrupt::disable();
+    tear_irq(Interrupt::Timer0Cmp);
+    tear_irq(Interrupt::Timer1Cmp);
+
+    let count = COUNTER.lock(&task, |c| *c);
+    sprintln!("count: {}", count);
+    if count == 2 * TICKS {
+        signal_pass(Some(&mut serial))
+    } else {
+        signal_fail(Some(&mut serial))
+    }
+    loop {}
+}
+
+/// Counts a tick of timer `idx`, returns `false` when the timer is done
+fn tick<const LEVEL: u8>(task: &Task<LEVEL>, idx: usize) -> bool {
+    TICKS_LEFT.lock(task, |left| {
+        left[idx] = left[idx].saturating_sub(1);
+        // Nested lock, read-modify-write would lose updates without it
+        COUNTER.lock(task, |c| *c += 1);
+        left[idx] != 0
+    })
+}
+
+#[nested_interrupt]
+fn Timer0Cmp() {
+    // SAFETY: Timer0Cmp is configured at LO_LEVEL
+    let task = unsafe { Task::<LO_LEVEL>::new() };
+    if !tick(&task, 0) {
+        unsafe { Timer::instance::<TIMER0_ADDR>() }.disable();
+    }
+}
+
+#[nested_interrupt]
+fn Timer1Cmp() {
+    // SAFETY: Timer1Cmp is configured at HI_LEVEL
+    let task = unsafe { Task::<HI_LEVEL>::new() };
+    if !tick(&task, 1) {
+        unsafe { Timer::instance::<TIMER1_ADDR>() }.disable();
+    }
+}
+
+fn setup_irq(irq: Interrupt, level: u8) {
+    unsafe {
+        Clic::configure(irq)
+            .trigger(Trig::Edge)
+            .polarity(Polarity::Pos)
+            .shv(true)
+            .level(level)
+            .enable()
+            .keep()
+    };
+}
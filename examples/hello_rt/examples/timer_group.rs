@@ -4,26 +4,22 @@
 #![no_std]
 
 use bsp::{
-    asm_delay,
-    mmap::apb_timer::{TIMER0_ADDR, TIMER1_ADDR, TIMER2_ADDR, TIMER3_ADDR},
-    rt::entry,
-    sprint, sprintln,
-    timer_group::Timer,
-    uart::*,
-    CPU_FREQ, NOPS_PER_SEC,
+    asm_delay, rt::entry, sprint, sprintln, timer_group::Timer, uart::*, Peripherals, CPU_FREQ,
+    NOPS_PER_SEC,
 };
 use hello_rt::{print_example_name, UART_BAUD};
 
 #[entry]
 fn main() -> ! {
-    let _serial = ApbUart::init(CPU_FREQ, UART_BAUD);
+    let p = Peripherals::take().unwrap();
+    let _serial = ApbUart::new(p.uart, CPU_FREQ, UartConfig::new(UART_BAUD));
     print_example_name!();
 
     let timers = &mut [
-        Timer::init::<TIMER0_ADDR>(),
-        Timer::init::<TIMER1_ADDR>(),
-        Timer::init::<TIMER2_ADDR>(),
-        Timer::init::<TIMER3_ADDR>(),
+        Timer::from(p.timer0),
+        Timer::from(p.timer1),
+        Timer::from(p.timer2),
+        Timer::from(p.timer3),
     ];
     timers[0].enable_with_prescaler(0);
     timers[1].enable_with_prescaler(1);
@@ -15,7 +15,7 @@ use bsp::{
     tb::signal_pass,
     timer_group::Timer,
     uart::*,
-    Interrupt, CPU_FREQ, NOPS_PER_SEC,
+    Interrupt, Peripherals, CPU_FREQ, NOPS_PER_SEC,
 };
 use hello_rt::{function, print_example_name, setup_irq, tear_irq, UART_BAUD};
 
@@ -32,7 +32,8 @@ static mut TIMEOUT: bool = false;
 
 #[entry]
 fn main() -> ! {
-    let mut serial = ApbUart::init(CPU_FREQ, UART_BAUD);
+    let p = Peripherals::take().unwrap();
+    let mut serial = ApbUart::new(p.uart, CPU_FREQ, UartConfig::new(UART_BAUD));
     print_example_name!();
 
     // Set level bits to 8
@@ -46,14 +47,14 @@ fn main() -> ! {
     setup_irq(Interrupt::MachineTimer);
 
     // Use mtimer for timeout
-    let mut mtimer = MTimer::instance();
+    let mut mtimer = MTimer::new(p.mtimer);
     mtimer.set_cmp(5 * INTERVAL as u64);
 
     let timers = &mut [
-        Timer::init::<TIMER0_ADDR>(),
-        Timer::init::<TIMER1_ADDR>(),
-        Timer::init::<TIMER2_ADDR>(),
-        Timer::init::<TIMER3_ADDR>(),
+        Timer::from(p.timer0),
+        Timer::from(p.timer1),
+        Timer::from(p.timer2),
+        Timer::from(p.timer3),
     ];
     timers[0].set_cmp(INTERVAL);
     timers[1].set_cmp(2 * INTERVAL);
@@ -139,5 +140,5 @@ fn Timer3Cmp() {
 #[interrupt]
 fn MachineTimer() {
     unsafe { TIMEOUT = true };
-    MTimer::instance().reset();
+    unsafe { MTimer::instance() }.reset();
 }
@@ -0,0 +1,100 @@
+//! Runs many software timers on the single mtimer compare
+//!
+//! Four periodic timers count their expirations until a one-shot timer stops
+//! the test. Each periodic timer must have expired as many times as its period
+//! fits into the test duration.
+#![no_main]
+#![no_std]
+#![allow(non_snake_case)]
+
+use core::{
+    ptr,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use bsp::{
+    clic::Clic,
+    mtimer::{
+        queue::{self, Action},
+        Duration, MTimer, Monotonic,
+    },
+    riscv::{self, asm::wfi},
+    rt::{entry, interrupt},
+    sprintln, tb,
+    uart::{ApbUart, UartConfig},
+    Interrupt, Peripherals, CPU_FREQ,
+};
+use hello_rt::{print_example_name, tear_irq, UART_BAUD};
+
+const PERIODS: [Duration; 4] = [
+    Duration::micros(100),
+    Duration::micros(200),
+    Duration::micros(300),
+    Duration::micros(400),
+];
+const TEST_DURATION: Duration = Duration::micros(1_250);
+
+static mut COUNTS: [u32; 4] = [0; 4];
+static STOP: AtomicBool = AtomicBool::new(false);
+
+#[entry]
+fn main() -> ! {
+    let p = Peripherals::take().unwrap();
+    let mut serial = ApbUart::new(p.uart, CPU_FREQ, UartConfig::new(UART_BAUD));
+    print_example_name!();
+
+    // Set level bits to 8
+    Clic::smclicconfig().set_mnlbits(8);
+
+    Monotonic::start(MTimer::new(p.mtimer));
+    unsafe { queue::enable_interrupt(0x88) };
+
+    let callbacks: [fn(); 4] = [tick0, tick1, tick2, tick3];
+    for (period, callback) in PERIODS.iter().zip(callbacks) {
+        queue::schedule_periodic(*period, Action::Callback(callback)).unwrap();
+    }
+    queue::schedule(TEST_DURATION, Action::Flag(&STOP)).unwrap();
+
+    unsafe { riscv::interrupt::enable() };
+    while !STOP.load(Ordering::Acquire) {
+        wfi();
+    }
+    riscv::interrupt::disable();
+    tear_irq(Interrupt::MachineTimer);
+
+    let counts = unsafe { *ptr::addr_of!(COUNTS) };
+    let mut ok = true;
+    for (idx, (period, count)) in PERIODS.iter().zip(counts).enumerate() {
+        let expected = (TEST_DURATION.ticks() / period.ticks()) as u32;
+        sprintln!("timer {}: {} (expected {})", idx, count, expected);
+        ok &= count == expected;
+    }
+
+    if ok {
+        tb::signal_pass(Some(&mut serial))
+    } else {
+        tb::signal_fail(Some(&mut serial))
+    }
+    loop {}
+}
+
+fn tick0() {
+    unsafe { COUNTS[0] += 1 };
+}
+
+fn tick1() {
+    unsafe { COUNTS[1] += 1 };
+}
+
+fn tick2() {
+    unsafe { COUNTS[2] += 1 };
+}
+
+fn tick3() {
+    unsafe { COUNTS[3] += 1 };
+}
+
+#[interrupt]
+fn MachineTimer() {
+    queue::on_interrupt();
+}
@@ -15,7 +15,7 @@ use bsp::{
     riscv::{self, asm::wfi},
     rt::entry,
     uart::*,
-    unmask_u32, Interrupt, CPU_FREQ,
+    unmask_u32, Interrupt, Peripherals, CPU_FREQ,
 };
 use hello_rt::{print_example_name, tear_irq, UART_BAUD};
 
@@ -39,7 +39,8 @@ const TIMEOUT: mtimer::Duration = mtimer::Duration::micros_at_least(5);
 
 #[entry]
 fn main() -> ! {
-    let _serial = ApbUart::init(CPU_FREQ, UART_BAUD);
+    let p = Peripherals::take().unwrap();
+    let _serial = ApbUart::new(p.uart, CPU_FREQ, UartConfig::new(UART_BAUD));
     print_example_name!();
 
     // Set level bits to 8
@@ -59,7 +60,7 @@ fn main() -> ! {
     enable_pcs(Interrupt::Dma5);
 
     // Use mtimer for timeout
-    let mut mtimer = MTimer::instance().into_oneshot();
+    let mut mtimer = MTimer::new(p.mtimer).into_oneshot();
 
     unsafe { riscv::interrupt::enable() };
     mtimer.start(TIMEOUT);
@@ -148,11 +149,15 @@ _start_Dma5_trap:
 
 #[inline]
 fn setup_irq(irq: Interrupt, level: u8) {
-    Clic::attr(irq).set_trig(Trig::Edge);
-    Clic::attr(irq).set_polarity(Polarity::Pos);
-    Clic::attr(irq).set_shv(true);
-    Clic::ctl(irq).set_level(level);
-    unsafe { Clic::ie(irq).enable() };
+    unsafe {
+        Clic::configure(irq)
+            .trigger(Trig::Edge)
+            .polarity(Polarity::Pos)
+            .shv(true)
+            .level(level)
+            .enable()
+            .keep()
+    };
 }
 
 /// Timeout interrupt (per test-run)
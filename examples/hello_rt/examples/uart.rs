@@ -9,12 +9,13 @@
 #![no_main]
 #![no_std]
 
-use bsp::{asm_delay, rt::entry, uart::*, CPU_FREQ};
+use bsp::{asm_delay, rt::entry, uart::*, Peripherals, CPU_FREQ};
 use hello_rt::UART_BAUD;
 
 #[entry]
 fn main() -> ! {
-    let mut serial = ApbUart::init(CPU_FREQ, UART_BAUD);
+    let p = Peripherals::take().unwrap();
+    let mut serial = ApbUart::new(p.uart, CPU_FREQ, UartConfig::new(UART_BAUD));
 
     serial.write_str("\r\n");
     serial.write_str("[UART] Hello from mock UART (Rust)!\r\n");
@@ -0,0 +1,58 @@
+//! Echoes bytes received over UART using the interrupt-driven, buffered driver
+#![no_std]
+#![no_main]
+#![allow(non_snake_case)]
+
+use bsp::{
+    clic::Clic,
+    embedded_io::{Read, ReadReady, Write},
+    riscv,
+    rt::{entry, interrupt},
+    uart::{buffered, buffered::BufferedUart, ApbUart, UartConfig},
+    Peripherals, CPU_FREQ,
+};
+use hello_rt::{print_example_name, UART_BAUD};
+
+#[entry]
+fn main() -> ! {
+    let p = Peripherals::take().unwrap();
+    let _serial = ApbUart::new(p.uart, CPU_FREQ, UartConfig::new(UART_BAUD));
+    print_example_name!();
+
+    // Set level bits to 8
+    Clic::smclicconfig().set_mnlbits(8);
+
+    let mut serial = BufferedUart::new(unsafe { ApbUart::instance() });
+    unsafe {
+        serial.enable_interrupt(0x88);
+        riscv::interrupt::enable();
+    }
+
+    serial
+        .write_all(b"Input characters to echo them back ('q' to exit)\r\n")
+        .unwrap();
+
+    let mut buf = [0u8; 16];
+    'echo: loop {
+        if !serial.read_ready().unwrap() {
+            riscv::asm::wfi();
+            continue;
+        }
+        let n = serial.read(&mut buf).unwrap();
+        serial.write_all(&buf[..n]).unwrap();
+        if buf[..n].contains(&b'q') {
+            break 'echo;
+        }
+    }
+    serial.flush().unwrap();
+
+    riscv::interrupt::disable();
+    let _serial = serial.free();
+
+    loop {}
+}
+
+#[interrupt]
+fn Uart() {
+    buffered::on_interrupt();
+}
@@ -9,14 +9,15 @@
 #![no_main]
 #![no_std]
 
-use bsp::{rt::entry, uart::*, CPU_FREQ};
+use bsp::{rt::entry, uart::*, Peripherals, CPU_FREQ};
 use bsp::{sprint, sprintln};
 use heapless::Vec;
 use hello_rt::UART_BAUD;
 
 #[entry]
 fn main() -> ! {
-    let mut serial = ApbUart::init(CPU_FREQ, UART_BAUD);
+    let p = Peripherals::take().unwrap();
+    let mut serial = ApbUart::new(p.uart, CPU_FREQ, UartConfig::new(UART_BAUD));
 
     sprintln!("\r\n[uart_echo]");
 
@@ -11,28 +11,37 @@
 use core::sync::atomic::{AtomicBool, Ordering};
 
 use bsp::{
-    clic::Clic,
+    clic::{Clic, Polarity, Trig},
     riscv::{self},
     rt::{entry, interrupt},
     sprintln,
-    uart::{ApbUart, UartInterrupt},
-    Interrupt, CPU_FREQ,
+    uart::{ApbUart, UartConfig, UartInterrupt},
+    Interrupt, Peripherals, CPU_FREQ,
 };
-use hello_rt::{setup_irq, tear_irq, UART_BAUD};
+use hello_rt::UART_BAUD;
 
 static mut UART: Option<ApbUart> = None;
 static mut RUNNING: AtomicBool = AtomicBool::new(true);
 
 #[entry]
 fn main() -> ! {
-    let mut serial = ApbUart::init(CPU_FREQ, UART_BAUD);
+    let p = Peripherals::take().unwrap();
+    let mut serial = ApbUart::new(p.uart, CPU_FREQ, UartConfig::new(UART_BAUD));
 
     sprintln!("\r\n[uart_irq]");
 
     // Set level bits to 8
     Clic::smclicconfig().set_mnlbits(8);
 
-    setup_irq(Interrupt::Uart);
+    // The previous configuration is restored when `irq` is dropped
+    let irq = unsafe {
+        Clic::configure(Interrupt::Uart)
+            .trigger(Trig::Edge)
+            .polarity(Polarity::Pos)
+            .shv(true)
+            .level(0x88)
+            .enable()
+    };
 
     // Raise an interrupt when a byte is available
     serial.listen(UartInterrupt::OnData);
@@ -55,7 +64,7 @@ fn main() -> ! {
     }
 
     // Clean up
-    tear_irq(Interrupt::Uart);
+    drop(irq);
     riscv::interrupt::disable();
 
     loop {}
@@ -0,0 +1,32 @@
+//! Echoes bytes over UART, reporting line errors and inactivity
+#![no_main]
+#![no_std]
+
+use bsp::{
+    embedded_io::Write,
+    mtimer::{Duration, MTimer, Monotonic},
+    rt::entry,
+    sprintln,
+    uart::*,
+    Peripherals, CPU_FREQ,
+};
+use hello_rt::{print_example_name, UART_BAUD};
+
+#[entry]
+fn main() -> ! {
+    let p = Peripherals::take().unwrap();
+    let mut serial = ApbUart::new(p.uart, CPU_FREQ, UartConfig::new(UART_BAUD));
+    print_example_name!();
+
+    Monotonic::start(MTimer::new(p.mtimer));
+
+    let mut buf = [0u8; 16];
+    sprintln!("Type something (5 s timeout): ");
+    loop {
+        match serial.read_with_deadline(&mut buf, Duration::secs(5)) {
+            Ok(0) => sprintln!("\r\ntimeout"),
+            Ok(n) => serial.write_all(&buf[..n]).unwrap(),
+            Err(e) => sprintln!("\r\nline error: {:?}", e),
+        }
+    }
+}
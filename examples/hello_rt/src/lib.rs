@@ -19,11 +19,15 @@ pub const UART_BAUD: u32 = if cfg!(feature = "rtl-tb") {
 /// Copy and customize this function if you need more involved configurations.
 pub fn setup_irq(irq: Interrupt) {
     sprintln!("Set up {:?} (id = {})", irq, irq.number());
-    Clic::attr(irq).set_trig(Trig::Edge);
-    Clic::attr(irq).set_polarity(Polarity::Pos);
-    Clic::attr(irq).set_shv(true);
-    Clic::ctl(irq).set_level(0x88);
-    unsafe { Clic::ie(irq).enable() };
+    unsafe {
+        Clic::configure(irq)
+            .trigger(Trig::Edge)
+            .polarity(Polarity::Pos)
+            .shv(true)
+            .level(0x88)
+            .enable()
+            .keep()
+    };
 }
 
 /// Tear down the IRQ configuration to avoid side-effects for further testing
@@ -31,11 +35,13 @@ pub fn setup_irq(irq: Interrupt) {
 /// Copy and customize this function if you need more involved configurations.
 pub fn tear_irq(irq: Interrupt) {
     sprintln!("Tear down {:?} (id = {})", irq, irq.number());
-    Clic::ie(irq).disable();
-    Clic::ctl(irq).set_level(0x0);
-    Clic::attr(irq).set_shv(false);
-    Clic::attr(irq).set_trig(Trig::Level);
-    Clic::attr(irq).set_polarity(Polarity::Pos);
+    Clic::configure(irq)
+        .trigger(Trig::Level)
+        .polarity(Polarity::Pos)
+        .shv(false)
+        .level(0x0)
+        .apply()
+        .keep();
 }
 
 /// Print the name of the current file, i.e., test name.
@@ -4,7 +4,7 @@
 #![allow(non_snake_case)]
 
 use core::arch::asm;
-use fugit::ExtU32;
+use fugit::ExtU64;
 use more_asserts as ma;
 
 use bsp::{
@@ -17,13 +17,14 @@ use bsp::{
     },
     mtimer::{self, MTimer},
     read_u32,
+    register::{mcycle, minstret},
     riscv::{self, asm::wfi},
     rt::entry,
     sprint, sprintln,
     tb::signal_pass,
     timer_group::{Periodic, Timer},
     uart::*,
-    write_u32, Interrupt, CPU_FREQ,
+    write_u32, Interrupt, Peripherals, CPU_FREQ,
 };
 use ufmt::derive::uDebug;
 
@@ -90,7 +91,8 @@ fn main() -> ! {
     // peripherals
     write_u32(CFG_BASE + PERIPH_CLK_DIV_OFS, PERIPH_CLK_DIV as u32);
 
-    let mut serial = ApbUart::init(CPU_FREQ, 115_200);
+    let p = Peripherals::take().unwrap();
+    let mut serial = ApbUart::new(p.uart, CPU_FREQ, UartConfig::new(115_200));
     sprintln!("[periodic_tasks (PCS={:?})]", cfg!(feature = "pcs"));
     sprintln!(
         "Periph CLK div = {}",
@@ -127,6 +129,15 @@ fn main() -> ! {
         Clic::ie(Interrupt::Timer3Cmp).set_pcs(true);
     }
 
+    // Use mtimer for timeout
+    let mut mtimer = MTimer::new(p.mtimer).into_oneshot();
+    let timers = &mut [
+        Timer::from(p.timer0).into_periodic(),
+        Timer::from(p.timer1).into_periodic(),
+        Timer::from(p.timer2).into_periodic(),
+        Timer::from(p.timer3).into_periodic(),
+    ];
+
     for run_idx in 0..RUN_COUNT {
         sprintln!("Run {}", run_idx);
         // SAFETY: interrupts off
@@ -140,29 +151,26 @@ fn main() -> ! {
             // Make sure serial is done printing before proceeding to the test case
             serial.flush().unwrap_unchecked();
         }
-        // Use mtimer for timeout
-        let mut mtimer = MTimer::instance().into_oneshot();
-
-        let timers = &mut [
-            Timer::init::<TIMER0_ADDR>().into_periodic(),
-            Timer::init::<TIMER1_ADDR>().into_periodic(),
-            Timer::init::<TIMER2_ADDR>().into_periodic(),
-            Timer::init::<TIMER3_ADDR>().into_periodic(),
-        ];
-
-        timers[0].set_period(TASK0.period_ns.nanos());
-        timers[1].set_period(TASK1.period_ns.nanos());
-        timers[2].set_period(TASK2.period_ns.nanos());
-        timers[3].set_period(TASK3.period_ns.nanos());
+        // Setting the period also resets the counter, the timers were disabled
+        // by the previous run
+        timers[0]
+            .set_period((TASK0.period_ns as u64).nanos())
+            .unwrap();
+        timers[1]
+            .set_period((TASK1.period_ns as u64).nanos())
+            .unwrap();
+        timers[2]
+            .set_period((TASK2.period_ns as u64).nanos())
+            .unwrap();
+        timers[3]
+            .set_period((TASK3.period_ns as u64).nanos())
+            .unwrap();
 
         // --- Test critical ---
-        unsafe {
-            asm!("fence");
-            // clear mcycle, minstret at start of critical section
-            asm!("csrw 0xB00, {0}", in(reg) 0x0);
-            asm!("csrw 0xB02, {0}", in(reg) 0x0);
-            /* !!! mcycle and minstret are missing write-methdods in BSP !!! */
-        };
+        unsafe { asm!("fence") };
+        // clear mcycle, minstret at start of critical section
+        mcycle::write64(0);
+        minstret::write64(0);
 
         // Test will end when MachineTimer fires
         mtimer.start(TEST_DURATION);
@@ -181,11 +189,8 @@ fn main() -> ! {
         // --- Test critical end ---
 
         unsafe {
-            let mcycle = riscv::register::mcycle::read64();
-            let minstret = riscv::register::minstret::read64();
-
-            sprintln!("cycles: {}", mcycle);
-            sprintln!("instrs: {}", minstret);
+            sprintln!("cycles: {}", mcycle::read64());
+            sprintln!("instrs: {}", minstret::read64());
             sprintln!(
                 "Task counts:\r\n{} | {} | {} | {}",
                 TASK0_COUNT,
@@ -377,18 +382,24 @@ unsafe fn MachineTimer() {
 }
 
 pub fn setup_irq(irq: Interrupt, level: u8) {
-    Clic::attr(irq).set_trig(Trig::Edge);
-    Clic::attr(irq).set_polarity(Polarity::Pos);
-    Clic::attr(irq).set_shv(true);
-    Clic::ctl(irq).set_level(level);
-    unsafe { Clic::ie(irq).enable() };
+    unsafe {
+        Clic::configure(irq)
+            .trigger(Trig::Edge)
+            .polarity(Polarity::Pos)
+            .shv(true)
+            .level(level)
+            .enable()
+            .keep()
+    };
 }
 
 /// Tear down the IRQ configuration to avoid side-effects for further testing
 pub fn tear_irq(irq: Interrupt) {
-    Clic::ie(irq).disable();
-    Clic::ctl(irq).set_level(0x0);
-    Clic::attr(irq).set_shv(false);
-    Clic::attr(irq).set_trig(Trig::Level);
-    Clic::attr(irq).set_polarity(Polarity::Pos);
+    Clic::configure(irq)
+        .trigger(Trig::Level)
+        .polarity(Polarity::Pos)
+        .shv(false)
+        .level(0x0)
+        .apply()
+        .keep();
 }
@@ -50,21 +50,7 @@ typedef enum integer {
   TGIrqIdBase = 21  // apb_timer interrupt can have multiple irq lines (2 per timer group)
 } clic_int_ids_e;
 
-// INCLUSIVE END ADDR
-localparam int unsigned GpioStartAddr     = 32'h0003_0000;
-localparam int unsigned GpioEndAddr       = 32'h0003_00FF;
-localparam int unsigned UartStartAddr     = 32'h0003_0100;
-localparam int unsigned UartEndAddr       = 32'h0003_01FF;
-localparam int unsigned MTimerStartAddr   = 32'h0003_0200;
-localparam int unsigned MTimerEndAddr     = 32'h0003_0210;
-localparam int unsigned ApbTimerStartAddr = 32'h0003_0300;
-localparam int unsigned ApbTimerEndAddr   = 32'h0003_03FF;
-localparam int unsigned ApbSpiMasterStartAddr = 32'h0003_0400;
-localparam int unsigned ApbSpiMasterEndAddr   = 32'h0003_04FF;
-localparam int unsigned CfgRegsStartAddr  = 32'h0003_0500;
-localparam int unsigned CfgRegsEndAddr    = 32'h0003_05FF;
-localparam int unsigned ClicStartAddr     = 32'h0005_0000;
-localparam int unsigned ClicEndAddr       = 32'h0005_FFFF;
+// APB address map is defined in rt_pkg
 
 localparam int unsigned NrApbPerip    = 7;
 localparam int unsigned SelWidth      = $clog2(NrApbPerip);
@@ -197,13 +183,13 @@ always_comb
       [rt_pkg::ClicStartAddr:rt_pkg::ClicEndAddr]: begin
         demux_sel = SelWidth'('h3);
       end
-      [ApbSpiMasterStartAddr:ApbSpiMasterEndAddr]: begin
+      [rt_pkg::SpiStartAddr:rt_pkg::SpiEndAddr]: begin
         demux_sel = SelWidth'('h4);
       end
-      [ApbTimerStartAddr:ApbTimerEndAddr]: begin
+      [rt_pkg::ApbTimerStartAddr:rt_pkg::ApbTimerEndAddr]: begin
         demux_sel = SelWidth'('h5);
       end
-      [CfgRegsStartAddr:CfgRegsEndAddr]: begin
+      [rt_pkg::CfgRegsStartAddr:rt_pkg::CfgRegsEndAddr]: begin
         demux_sel = SelWidth'('h6);
       end
       default: begin
@@ -23,16 +23,20 @@ localparam addr_rule_t AxiRule  = '{ Start: 32'hFFFF_0000, End: 32'h0000_0000 };
 
 
 // APB MAPPING, INCLUSIVE END ADDR
-localparam int unsigned GpioStartAddr   = 32'h0003_0000;
-localparam int unsigned GpioEndAddr     = 32'h0003_006F;
-localparam int unsigned UartStartAddr   = 32'h0003_0100;
-localparam int unsigned UartEndAddr     = 32'h0003_01FF;
-localparam int unsigned MTimerStartAddr = 32'h0003_0200;
-localparam int unsigned MTimerEndAddr   = 32'h0003_0210;
-localparam int unsigned ClicStartAddr   = 32'h0005_0000;
-localparam int unsigned ClicEndAddr     = 32'h0005_FFFF;
-localparam int unsigned SpiStartAddr    = 32'h0006_0000;
-localparam int unsigned SpiEndAddr      = 32'h0006_FFFF;
+localparam int unsigned GpioStartAddr     = 32'h0003_0000;
+localparam int unsigned GpioEndAddr       = 32'h0003_006F;
+localparam int unsigned UartStartAddr     = 32'h0003_0100;
+localparam int unsigned UartEndAddr       = 32'h0003_01FF;
+localparam int unsigned MTimerStartAddr   = 32'h0003_0200;
+localparam int unsigned MTimerEndAddr     = 32'h0003_0210;
+localparam int unsigned ApbTimerStartAddr = 32'h0003_0300;
+localparam int unsigned ApbTimerEndAddr   = 32'h0003_03FF;
+localparam int unsigned SpiStartAddr      = 32'h0003_0400;
+localparam int unsigned SpiEndAddr        = 32'h0003_04FF;
+localparam int unsigned CfgRegsStartAddr  = 32'h0003_0500;
+localparam int unsigned CfgRegsEndAddr    = 32'h0003_05FF;
+localparam int unsigned ClicStartAddr     = 32'h0005_0000;
+localparam int unsigned ClicEndAddr       = 32'h0005_FFFF;
 
 localparam int unsigned NumMemBanks = 1;
 localparam int unsigned NumDMAs     = 1;